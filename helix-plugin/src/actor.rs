@@ -0,0 +1,249 @@
+//! A dedicated-thread plugin runtime.
+//!
+//! `PluginManager`/`LuaEngine` run scripts inline on the main UI loop, so a
+//! slow or looping plugin freezes the editor. [`PluginActor`] instead owns
+//! its own `mlua::Lua` VM on a private OS thread (the VM is not `Send` and
+//! must never cross it) and talks to the main thread over two bounded
+//! `crossbeam-channel`s: [`Job`]s go in, [`JobResult`]s come back, each job
+//! bounded by a per-call deadline so a runaway script can be killed instead
+//! of taking the editor down with it.
+//!
+//! Because editor mutation must stay serialized on the main loop, the Lua
+//! functions the actor exposes don't touch an `Editor` directly; instead
+//! they send an [`EditorOp`] over a reply channel and block until the main
+//! loop (which calls [`PluginActor::drain_ops`] between frames) answers.
+//! `crate::PluginManager::execute_command_on_actor` is the entry point that
+//! spawns and drives one of these: it submits a [`Job::CallCommand`], then
+//! drains `EditorOp`s against the live editor in a loop until the job
+//! completes or the per-call deadline trips.
+
+use crate::error::PluginError;
+use crate::types::EditorCommandRegistry;
+use crossbeam_channel::{bounded, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Work submitted to the actor thread.
+pub enum Job {
+    LoadScript { name: String, code: String },
+    InvokeHook { name: String, args: Vec<String> },
+    CallCommand { name: String, args: Vec<String> },
+}
+
+/// Outcome of a [`Job`], sent back over the result channel.
+pub enum JobResult {
+    Ok,
+    Err(String),
+}
+
+/// A request from the actor thread to mutate or read `&mut Editor`, answered
+/// by the main loop from [`PluginActor::drain_ops`].
+pub enum EditorOp {
+    GetCursor,
+    SetSelection { anchor: usize, head: usize },
+    ExecuteCommand { name: String, args: Vec<String> },
+}
+
+/// Reply to an [`EditorOp`].
+pub enum EditorOpResult {
+    Cursor(usize),
+    Unit,
+    Error(String),
+}
+
+type OpRequest = (EditorOp, Sender<EditorOpResult>);
+
+/// Handle to a running plugin actor thread.
+pub struct PluginActor {
+    job_tx: Option<Sender<Job>>,
+    result_rx: Receiver<JobResult>,
+    op_rx: Receiver<OpRequest>,
+    handle: Option<JoinHandle<()>>,
+    /// Dispatches `EditorOp::ExecuteCommand`, answered from [`Self::drain_ops`]
+    /// on the main thread (the same registry the synchronous Lua API uses).
+    commands: Arc<dyn EditorCommandRegistry>,
+}
+
+impl PluginActor {
+    /// Spawn the actor thread. `deadline` bounds how long a single [`Job`]
+    /// is allowed to run before it is reported as [`PluginError::Timeout`].
+    pub fn spawn(deadline: Duration, commands: Arc<dyn EditorCommandRegistry>) -> Self {
+        let (job_tx, job_rx) = bounded::<Job>(32);
+        let (result_tx, result_rx) = bounded::<JobResult>(32);
+        let (op_tx, op_rx) = bounded::<OpRequest>(32);
+
+        let handle = std::thread::Builder::new()
+            .name("hx-plugin-actor".to_string())
+            .spawn(move || Self::run(job_rx, result_tx, op_tx, deadline))
+            .expect("failed to spawn plugin actor thread");
+
+        Self {
+            job_tx: Some(job_tx),
+            result_rx,
+            op_rx,
+            handle: Some(handle),
+            commands,
+        }
+    }
+
+    /// Submit a job to run on the actor thread. Blocks briefly if the job
+    /// queue is full (it is bounded, so a wedged actor applies backpressure
+    /// rather than growing without limit).
+    pub fn submit(&self, job: Job) -> std::result::Result<(), crossbeam_channel::SendError<Job>> {
+        self.job_tx
+            .as_ref()
+            .expect("actor not yet shut down")
+            .send(job)
+    }
+
+    /// Non-blocking poll for a completed job's result.
+    pub fn try_recv_result(&self) -> Option<JobResult> {
+        self.result_rx.try_recv().ok()
+    }
+
+    /// Issue an `EditorOp` request to the currently running script and block
+    /// for a reply. Called from the actor thread (never the main thread).
+    fn request(op_tx: &Sender<OpRequest>, op: EditorOp) -> EditorOpResult {
+        let (reply_tx, reply_rx) = bounded(1);
+        if op_tx.send((op, reply_tx)).is_err() {
+            return EditorOpResult::Error("editor op channel closed".to_string());
+        }
+        reply_rx
+            .recv()
+            .unwrap_or_else(|_| EditorOpResult::Error("editor op reply channel closed".to_string()))
+    }
+
+    /// Drain pending `EditorOp` requests and apply them against the live
+    /// editor. Call this from the main loop between frames; it never blocks.
+    pub fn drain_ops(&self, editor: &mut helix_view::Editor) {
+        while let Ok((op, reply)) = self.op_rx.try_recv() {
+            let result = match op {
+                EditorOp::GetCursor => {
+                    let (view, doc): (&helix_view::View, &helix_view::Document) =
+                        helix_view::current_ref!(editor);
+                    let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+                    EditorOpResult::Cursor(cursor)
+                }
+                EditorOp::SetSelection { anchor, head } => {
+                    let (view, doc) = helix_view::current!(editor);
+                    doc.set_selection(view.id, helix_core::Selection::single(anchor, head));
+                    EditorOpResult::Unit
+                }
+                EditorOp::ExecuteCommand { name, args } => {
+                    match self.commands.execute(editor, &name, &args) {
+                        Ok(()) => EditorOpResult::Unit,
+                        Err(e) => EditorOpResult::Error(e.to_string()),
+                    }
+                }
+            };
+            let _ = reply.send(result);
+        }
+    }
+
+    /// The actor thread's main loop: owns the `Lua` VM for its entire
+    /// lifetime and never lets it cross to another thread.
+    fn run(job_rx: Receiver<Job>, result_tx: Sender<JobResult>, op_tx: Sender<OpRequest>, deadline: Duration) {
+        let lua = mlua::Lua::new();
+
+        let get_cursor_op_tx = op_tx.clone();
+        let get_cursor = lua.create_function(move |_, ()| {
+            match Self::request(&get_cursor_op_tx, EditorOp::GetCursor) {
+                EditorOpResult::Cursor(idx) => Ok(idx),
+                EditorOpResult::Error(e) => Err(mlua::Error::RuntimeError(e)),
+                EditorOpResult::Unit => Err(mlua::Error::RuntimeError("failed to fetch cursor".to_string())),
+            }
+        });
+        if let Ok(get_cursor) = get_cursor {
+            let _ = lua.globals().set("__hx_get_cursor", get_cursor);
+        }
+
+        let set_selection_op_tx = op_tx.clone();
+        let set_selection = lua.create_function(move |_, (anchor, head): (usize, usize)| {
+            match Self::request(&set_selection_op_tx, EditorOp::SetSelection { anchor, head }) {
+                EditorOpResult::Unit => Ok(()),
+                EditorOpResult::Error(e) => Err(mlua::Error::RuntimeError(e)),
+                EditorOpResult::Cursor(_) => Err(mlua::Error::RuntimeError(
+                    "unexpected reply to set_selection".to_string(),
+                )),
+            }
+        });
+        if let Ok(set_selection) = set_selection {
+            let _ = lua.globals().set("__hx_set_selection", set_selection);
+        }
+
+        let execute_command_op_tx = op_tx.clone();
+        let execute_command = lua.create_function(move |_, (name, args): (String, Vec<String>)| {
+            match Self::request(
+                &execute_command_op_tx,
+                EditorOp::ExecuteCommand { name, args },
+            ) {
+                EditorOpResult::Unit => Ok(()),
+                EditorOpResult::Error(e) => Err(mlua::Error::RuntimeError(e)),
+                EditorOpResult::Cursor(_) => Err(mlua::Error::RuntimeError(
+                    "unexpected reply to execute_command".to_string(),
+                )),
+            }
+        });
+        if let Ok(execute_command) = execute_command {
+            let _ = lua.globals().set("__hx_execute_command", execute_command);
+        }
+
+        // Checked every 1000 VM instructions, so a job that never returns on
+        // its own (`while true do end`) is aborted once it passes `deadline`.
+        let job_started = Arc::new(std::sync::Mutex::new(Instant::now()));
+        let hook_started = job_started.clone();
+        lua.set_hook(mlua::HookTriggers::new().every_nth_instruction(1000), move |_, _debug| {
+            let started = *hook_started.lock().unwrap_or_else(|e| e.into_inner());
+            if started.elapsed() > deadline {
+                return Err(mlua::Error::RuntimeError(format!("job exceeded {:?} deadline", deadline)));
+            }
+            Ok(mlua::VmState::Continue)
+        });
+
+        for job in job_rx.iter() {
+            let started = Instant::now();
+            *job_started.lock().unwrap_or_else(|e| e.into_inner()) = started;
+            let outcome = match job {
+                Job::LoadScript { name, code } => lua
+                    .load(&code)
+                    .set_name(&name)
+                    .exec()
+                    .map_err(|e| e.to_string()),
+                Job::InvokeHook { name, args } => Self::call_global(&lua, &name, args),
+                Job::CallCommand { name, args } => Self::call_global(&lua, &name, args),
+            };
+
+            let result = if started.elapsed() > deadline {
+                JobResult::Err(
+                    PluginError::Timeout(format!("job exceeded {:?} deadline", deadline)).to_string(),
+                )
+            } else {
+                match outcome {
+                    Ok(()) => JobResult::Ok,
+                    Err(e) => JobResult::Err(e),
+                }
+            };
+
+            if result_tx.send(result).is_err() {
+                break; // main thread hung up
+            }
+        }
+    }
+
+    fn call_global(lua: &mlua::Lua, name: &str, args: Vec<String>) -> std::result::Result<(), String> {
+        let func: mlua::Function = lua.globals().get(name).map_err(|e| e.to_string())?;
+        func.call::<()>(args).map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for PluginActor {
+    fn drop(&mut self) {
+        // Dropping the sender closes the channel, which ends `job_rx.iter()`
+        // on the actor thread so the `join` below doesn't deadlock.
+        self.job_tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
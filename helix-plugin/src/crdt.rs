@@ -0,0 +1,243 @@
+//! A small WOOT/RGA-style CRDT sequence used by the `helix.workspace`
+//! collaboration API (see [`crate::lua::api::workspace`]).
+//!
+//! Every inserted character is tagged with a globally unique [`CharId`] plus
+//! the ids of the neighbors it was inserted between. Merging a remote insert
+//! is commutative: the neighbor ids are located in the local sequence and the
+//! new character is placed deterministically among any concurrent inserts by
+//! comparing ids. Deletes never remove an entry outright; they tombstone it
+//! so concurrent inserts anchored to it still have somewhere to land.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Globally unique id for a single character: the site that inserted it plus
+/// a per-site monotonic counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CharId {
+    pub site_id: u64,
+    pub counter: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CrdtChar {
+    id: CharId,
+    left: Option<CharId>,
+    right: Option<CharId>,
+    value: char,
+    tombstone: bool,
+}
+
+/// An insert operation: a new character placed between two (possibly
+/// nonexistent, i.e. document start/end) neighbors.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct InsertOp {
+    pub id: CharId,
+    pub left: Option<CharId>,
+    pub right: Option<CharId>,
+    pub value: char,
+}
+
+/// A delete operation: tombstone the character with this id.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DeleteOp {
+    pub id: CharId,
+}
+
+/// An ordered, replicated character sequence.
+pub struct RgaSequence {
+    site_id: u64,
+    counter: u64,
+    chars: Vec<CrdtChar>,
+    index: HashMap<CharId, usize>,
+}
+
+impl RgaSequence {
+    pub fn new(site_id: u64) -> Self {
+        Self {
+            site_id,
+            counter: 0,
+            chars: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> CharId {
+        let id = CharId {
+            site_id: self.site_id,
+            counter: self.counter,
+        };
+        self.counter += 1;
+        id
+    }
+
+    /// Insert `value` at the given *visible* (tombstones excluded) offset,
+    /// returning the op to broadcast to peers.
+    pub fn local_insert(&mut self, visible_offset: usize, value: char) -> InsertOp {
+        let (left, right, insert_at) = self.neighbors_at(visible_offset);
+        let id = self.next_id();
+        self.chars.insert(
+            insert_at,
+            CrdtChar {
+                id,
+                left,
+                right,
+                value,
+                tombstone: false,
+            },
+        );
+        self.reindex_from(insert_at);
+        InsertOp {
+            id,
+            left,
+            right,
+            value,
+        }
+    }
+
+    /// Apply a remote insert. Commutative: if the same op (or its neighbors)
+    /// has already been merged this is a no-op for that id.
+    pub fn apply_insert(&mut self, op: InsertOp) {
+        if self.index.contains_key(&op.id) {
+            return; // already merged
+        }
+
+        // Anchor position: right after `left` (document start if None).
+        let mut pos = op
+            .left
+            .and_then(|l| self.index.get(&l).copied().map(|i| i + 1))
+            .unwrap_or(0);
+
+        // Walk forward past any concurrent inserts anchored at the same
+        // `left`, breaking ties by comparing ids so all replicas converge on
+        // the same total order regardless of arrival order.
+        while let Some(existing) = self.chars.get(pos) {
+            if existing.left != op.left {
+                break;
+            }
+            if existing.id > op.id {
+                break;
+            }
+            pos += 1;
+        }
+
+        self.chars.insert(
+            pos,
+            CrdtChar {
+                id: op.id,
+                left: op.left,
+                right: op.right,
+                value: op.value,
+                tombstone: false,
+            },
+        );
+        self.reindex_from(pos);
+    }
+
+    /// Tombstone the character at the given visible offset, returning the op
+    /// to broadcast to peers.
+    pub fn local_delete(&mut self, visible_offset: usize) -> Option<DeleteOp> {
+        let pos = self.visible_to_internal(visible_offset)?;
+        self.chars[pos].tombstone = true;
+        Some(DeleteOp {
+            id: self.chars[pos].id,
+        })
+    }
+
+    /// Apply a remote delete. Commutative: tombstoning twice is harmless.
+    pub fn apply_delete(&mut self, op: DeleteOp) {
+        if let Some(&pos) = self.index.get(&op.id) {
+            self.chars[pos].tombstone = true;
+        }
+    }
+
+    /// The visible (tombstone-excluded) offset of `id`, if it is currently
+    /// present and not tombstoned. Lets a caller that just merged a single
+    /// op find exactly where it landed, so it can apply that one character
+    /// as its own `Transaction` instead of diffing the whole sequence.
+    pub fn visible_index_of(&self, id: CharId) -> Option<usize> {
+        let pos = *self.index.get(&id)?;
+        if self.chars[pos].tombstone {
+            return None;
+        }
+        Some(self.chars[..pos].iter().filter(|c| !c.tombstone).count())
+    }
+
+    /// The current visible text.
+    pub fn text(&self) -> String {
+        self.chars
+            .iter()
+            .filter(|c| !c.tombstone)
+            .map(|c| c.value)
+            .collect()
+    }
+
+    fn visible_to_internal(&self, visible_offset: usize) -> Option<usize> {
+        self.chars
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.tombstone)
+            .nth(visible_offset)
+            .map(|(i, _)| i)
+    }
+
+    /// Returns (left neighbor id, right neighbor id, internal insertion index)
+    /// for a local insert at the given visible offset.
+    fn neighbors_at(&self, visible_offset: usize) -> (Option<CharId>, Option<CharId>, usize) {
+        if visible_offset == 0 {
+            let right = self.chars.iter().find(|c| !c.tombstone).map(|c| c.id);
+            return (None, right, 0);
+        }
+        match self.visible_to_internal(visible_offset - 1) {
+            Some(left_pos) => {
+                let left = self.chars[left_pos].id;
+                let right = self.chars[left_pos + 1..]
+                    .iter()
+                    .find(|c| !c.tombstone)
+                    .map(|c| c.id);
+                (Some(left), right, left_pos + 1)
+            }
+            None => (None, None, self.chars.len()),
+        }
+    }
+
+    fn reindex_from(&mut self, from: usize) {
+        for (i, c) in self.chars.iter().enumerate().skip(from) {
+            self.index.insert(c.id, i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_insert_and_delete_round_trip() {
+        let mut seq = RgaSequence::new(1);
+        seq.local_insert(0, 'h');
+        seq.local_insert(1, 'i');
+        assert_eq!(seq.text(), "hi");
+
+        seq.local_delete(0);
+        assert_eq!(seq.text(), "i");
+    }
+
+    #[test]
+    fn concurrent_inserts_converge_by_id() {
+        let mut a = RgaSequence::new(1);
+        let mut b = RgaSequence::new(2);
+
+        let base = a.local_insert(0, 'a');
+        b.apply_insert(base);
+
+        // Both sites insert right after 'a' concurrently.
+        let op_a = a.local_insert(1, 'x');
+        let op_b = b.local_insert(1, 'y');
+
+        a.apply_insert(op_b);
+        b.apply_insert(op_a);
+
+        assert_eq!(a.text(), b.text());
+    }
+}
@@ -42,6 +42,10 @@ pub enum PluginError {
     /// API access error
     #[error("API access error: {0}")]
     ApiAccessError(String),
+
+    /// A plugin job exceeded its configured time/instruction budget
+    #[error("Plugin exceeded its execution budget: {0}")]
+    Timeout(String),
 }
 
 /// Result type for plugin operations
@@ -1,8 +1,10 @@
 //! Helix Plugin System
 //!
-//! This crate provides a Lua-based plugin system for the Helix text editor.
-//! Plugins can register event handlers, custom commands, and interact with
-//! the editor through a safe API.
+//! This crate provides a plugin system for the Helix text editor, with Lua
+//! as the default runtime and an optional sandboxed WASM runtime
+//! ([`WasmEngine`]) for CPU-heavy or non-Lua plugins. Plugins can register
+//! event handlers, custom commands, and interact with the editor through a
+//! safe API.
 //!
 //! # Example Plugin
 //!
@@ -19,47 +21,122 @@
 //! end)
 //! ```
 
+pub mod actor;
+pub mod crdt;
 pub mod error;
 pub mod lua;
+pub mod runtime;
 pub mod types;
+pub mod wasm;
 
 // Re-exports
 pub use error::{PluginError, Result};
 pub use lua::LuaEngine;
+pub use runtime::PluginRuntime;
 pub use types::{
-    EventData, EventType, IndividualPluginConfig, Plugin, PluginConfig, PluginEvent, PluginMetadata,
+    EventData, EventType, IndividualPluginConfig, Plugin, PluginConfig, PluginEvent, PluginLimits,
+    PluginMetadata,
 };
+pub use wasm::WasmEngine;
 
 use helix_view::Editor;
 use log::info;
-use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// The main plugin manager
 pub struct PluginManager {
-    /// The Lua engine
-    engine: Arc<RwLock<LuaEngine>>,
+    /// One runtime per plugin language - currently Lua and WASM. Event and
+    /// command calls fan out across all of them; `load_plugin_from_path`
+    /// routes a plugin to whichever one's `handles` claims its entry point.
+    runtimes: Vec<Box<dyn PluginRuntime>>,
     /// Plugin configuration
     config: PluginConfig,
+    /// Dispatches `EditorOp::ExecuteCommand` once an actor is spawned; kept
+    /// around so [`Self::actor`] can lazily spawn [`actor::PluginActor`]
+    /// without needing it threaded through every call site.
+    builtin_commands: Option<Arc<dyn crate::types::EditorCommandRegistry>>,
+    /// Dedicated-thread runtime, spawned lazily the first time a caller asks
+    /// to run a command off the main loop via [`Self::execute_command_on_actor`],
+    /// so a slow or looping plugin invoked that way can't freeze the editor.
+    actor: Option<actor::PluginActor>,
 }
 
 impl PluginManager {
     /// Create a new plugin manager
     pub fn new(config: PluginConfig) -> Result<Self> {
-        let engine = LuaEngine::new()?;
-        engine.register_api(config.clone())?;
+        let runtimes = Self::build_runtimes(&config, None, None)?;
+        Ok(Self {
+            runtimes,
+            config,
+            builtin_commands: None,
+            actor: None,
+        })
+    }
 
+    /// Like [`Self::new`], but install `ui_handler`/`builtin_commands` on
+    /// the Lua engine before `register_api` runs. Setting them afterward
+    /// (the only option `new()` + [`LuaEngine::set_ui_handler`] leaves open)
+    /// is too late: `register_api` only copies whichever handlers are
+    /// present into Lua app data at the moment it runs. Chiefly useful for
+    /// tests (see `helix-plugin-test-support`), which need their mock
+    /// handlers wired in from the start.
+    pub fn with_handlers(
+        config: PluginConfig,
+        ui_handler: Option<Arc<dyn crate::types::UiHandler>>,
+        builtin_commands: Option<Arc<dyn crate::types::EditorCommandRegistry>>,
+    ) -> Result<Self> {
+        let runtimes = Self::build_runtimes(&config, ui_handler, builtin_commands.clone())?;
         Ok(Self {
-            engine: Arc::new(RwLock::new(engine)),
+            runtimes,
             config,
+            builtin_commands,
+            actor: None,
         })
     }
 
+    fn build_runtimes(
+        config: &PluginConfig,
+        ui_handler: Option<Arc<dyn crate::types::UiHandler>>,
+        builtin_commands: Option<Arc<dyn crate::types::EditorCommandRegistry>>,
+    ) -> Result<Vec<Box<dyn PluginRuntime>>> {
+        let mut lua_engine = LuaEngine::new()?;
+        if let Some(handler) = ui_handler {
+            lua_engine.set_ui_handler(handler);
+        }
+        if let Some(registry) = builtin_commands {
+            lua_engine.set_builtin_command_registry(registry);
+        }
+        lua_engine.register_api(config.clone())?;
+
+        let wasm_engine = WasmEngine::new()?;
+
+        Ok(vec![Box::new(lua_engine), Box::new(wasm_engine)])
+    }
+
+    /// Reach a specific runtime's concrete type for operations outside
+    /// [`PluginRuntime`] (e.g. `LuaEngine::lua()`). Returns `None` if no
+    /// loaded runtime downcasts to `T`.
+    pub fn runtime<T: PluginRuntime + 'static>(&self) -> Option<&T> {
+        self.runtimes
+            .iter()
+            .find_map(|runtime| runtime.as_any().downcast_ref::<T>())
+    }
+
     /// Returns true if the plugin system is enabled
     pub fn is_enabled(&self) -> bool {
         self.config.enabled
     }
 
+    /// Where the persistent plugin metadata cache lives, alongside plugin
+    /// storage under the config directory. One file per plugin lives under
+    /// this directory (see `lua::cache::PluginCache`).
+    fn cache_dir() -> std::path::PathBuf {
+        helix_loader::config_dir().join("plugin-cache")
+    }
+
     /// Initialize and load all plugins
     pub fn initialize(&mut self, editor: &mut Editor) -> Result<()> {
         // Determine plugin directories
@@ -71,29 +148,38 @@ impl PluginManager {
 
         info!("Searching for plugins in: {:?}", plugin_dirs);
 
-        // Discover plugins
-        let loader = lua::loader::PluginLoader::new(plugin_dirs);
-        let plugins = loader.discover_plugins()?;
+        // Discover plugins, reusing cached metadata for anything unchanged
+        // on disk so we skip a parse (and the directory walk stays cheap).
+        let loader = lua::loader::PluginLoader::new(plugin_dirs.clone());
+        let cache_dir = Self::cache_dir();
+        let mut cache = lua::cache::PluginCache::load(&cache_dir);
+        let plugins = loader.discover_plugins_cached(&cache)?;
+        let plugins = self.resolve_plugins(plugins, &plugin_dirs);
 
         info!("Discovered {} plugins", plugins.len());
 
-        // Load each plugin
-        let mut engine = self.engine.write();
+        // Load each plugin onto whichever runtime handles its entry point
         for plugin in plugins {
-            // Check if plugin is enabled in config
-            let enabled = self.is_plugin_enabled(&plugin.metadata.name);
+            info!("Loading plugin: {}", plugin.metadata.name);
+            let name = plugin.metadata.name.clone();
+            let cache_entry = loader.cache_entry(&plugin, Vec::new());
 
-            if !enabled {
-                info!("Skipping disabled plugin: {}", plugin.metadata.name);
+            let Some(runtime) = self.runtimes.iter_mut().find(|r| r.handles(&plugin)) else {
+                log::error!("No runtime can load plugin '{}'", name);
+                continue;
+            };
+            if let Err(e) = runtime.load_plugin(plugin) {
+                log::error!("Failed to load plugin: {}", e);
                 continue;
             }
 
-            info!("Loading plugin: {}", plugin.metadata.name);
-            if let Err(e) = engine.load_plugin(plugin) {
-                log::error!("Failed to load plugin: {}", e);
+            if let Some(mut entry) = cache_entry {
+                entry.commands = runtime.get_commands_for_plugin(&name);
+                if let Err(e) = cache.update(&cache_dir, name, entry) {
+                    log::warn!("Failed to write plugin metadata cache entry: {}", e);
+                }
             }
         }
-        drop(engine);
 
         // Fire init event
         self.fire_event(
@@ -107,50 +193,168 @@ impl PluginManager {
         Ok(())
     }
 
-    /// Check if a plugin is enabled in the configuration
-    fn is_plugin_enabled(&self, name: &str) -> bool {
-        // If there's specific config for this plugin, use that
-        if let Some(plugin_config) = self.config.plugins.iter().find(|p| p.name == name) {
-            return plugin_config.enabled;
+    /// Resolve configured plugin aliases/sources against `discovered`,
+    /// producing the final, enabled set of plugins to load. Mirrors
+    /// zellij's `RunPluginOrAlias`: once resolved, commands and event
+    /// subscriptions only ever see a plugin's `alias` (or its own name),
+    /// never which directory it came from - so `source` can point the same
+    /// directory at two config entries with two different aliases and
+    /// `config` blobs.
+    fn resolve_plugins(&self, discovered: Vec<Plugin>, plugin_dirs: &[PathBuf]) -> Vec<Plugin> {
+        let mut by_name: HashMap<String, Plugin> = discovered
+            .into_iter()
+            .map(|plugin| (plugin.metadata.name.clone(), plugin))
+            .collect();
+        let mut resolved = Vec::new();
+
+        for entry in &self.config.plugins {
+            if !entry.enabled {
+                info!("Skipping disabled plugin: {}", entry.name);
+                by_name.remove(&entry.name);
+                continue;
+            }
+
+            let mut plugin = if let Some(source) = &entry.source {
+                let path = Self::resolve_source_path(source, plugin_dirs);
+                match lua::loader::PluginLoader::new(vec![]).load_plugin_metadata(&path) {
+                    Ok(plugin) => plugin,
+                    Err(e) => {
+                        log::error!(
+                            "Failed to load plugin '{}' from source {:?}: {}",
+                            entry.name,
+                            path,
+                            e
+                        );
+                        continue;
+                    }
+                }
+            } else if let Some(plugin) = by_name.remove(&entry.name) {
+                plugin
+            } else {
+                continue;
+            };
+
+            if let Some(alias) = &entry.alias {
+                plugin.metadata.name = alias.clone();
+            }
+            resolved.push(plugin);
         }
 
-        // Otherwise, enabled by default
-        true
+        // Anything discovered with no matching config entry loads under its
+        // own discovered name, enabled by default.
+        resolved.extend(by_name.into_values());
+
+        resolved
     }
 
-    /// Fire an event to all registered handlers
+    /// Resolve an `IndividualPluginConfig::source` to a concrete path: used
+    /// as-is if it already points at something on disk, otherwise treated
+    /// as a bare name and looked up as a subdirectory of each configured
+    /// plugin directory in turn (the "well-known location" case).
+    fn resolve_source_path(source: &str, plugin_dirs: &[PathBuf]) -> PathBuf {
+        let path = PathBuf::from(source);
+        if path.exists() {
+            return path;
+        }
+
+        plugin_dirs
+            .iter()
+            .map(|dir| dir.join(source))
+            .find(|candidate| candidate.exists())
+            .unwrap_or(path)
+    }
+
+    /// Fire an event to every runtime's registered handlers
     pub fn fire_event(&self, editor: &mut Editor, event: PluginEvent) -> Result<()> {
-        let engine = self.engine.read();
-        engine.call_event_handlers(editor, &event)
+        for runtime in &self.runtimes {
+            runtime.call_event_handlers(editor, &event)?;
+        }
+        Ok(())
     }
 
-    /// Get plugin configuration for a specific plugin
+    /// Get plugin configuration for a specific plugin, looked up by its
+    /// effective name (`alias` if the config entry set one, else `name`).
     pub fn get_plugin_config(&self, name: &str) -> Option<&serde_json::Value> {
         self.config
             .plugins
             .iter()
-            .find(|p| p.name == name)
+            .find(|p| p.effective_name() == name)
             .map(|p| &p.config)
     }
 
-    /// Get the Lua engine (for advanced operations)
-    pub fn engine(&self) -> Arc<RwLock<LuaEngine>> {
-        Arc::clone(&self.engine)
-    }
-
-    /// Get registered commands
+    /// Get registered commands, across every runtime
     pub fn get_commands(&self) -> Vec<crate::types::CommandMetadata> {
-        self.engine.read().get_commands()
+        self.runtimes.iter().flat_map(|r| r.get_commands()).collect()
     }
 
-    /// Execute a plugin command
+    /// Execute a plugin command, trying each runtime in turn and returning
+    /// the first success. If every runtime fails, the last runtime's error
+    /// is returned, even when an earlier runtime had `name` and failed.
     pub fn execute_command(
         &self,
         editor: &mut Editor,
         name: &str,
         args: Vec<String>,
     ) -> Result<()> {
-        self.engine.read().execute_command(editor, name, args)
+        let mut last_err = None;
+        for runtime in &self.runtimes {
+            match runtime.execute_command(editor, name, args.clone()) {
+                Ok(()) => return Ok(()),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| PluginError::CommandExecutionFailed(format!("Command not found: {}", name))))
+    }
+
+    /// Lazily spawn (or reuse) the dedicated-thread actor, so a plugin can be
+    /// run off the main loop without every embedder having to spawn and own
+    /// one itself. Returns `None` if no `EditorCommandRegistry` was supplied
+    /// via [`Self::with_handlers`] - without one, `EditorOp::ExecuteCommand`
+    /// would have nothing to dispatch against.
+    fn actor(&mut self) -> Option<&actor::PluginActor> {
+        if self.actor.is_none() {
+            let commands = self.builtin_commands.clone()?;
+            let deadline = Duration::from_millis(self.config.limits.max_duration_ms);
+            self.actor = Some(actor::PluginActor::spawn(deadline, commands));
+        }
+        self.actor.as_ref()
+    }
+
+    /// Run `name` on the dedicated actor thread instead of inline on the
+    /// caller's thread: submits a [`actor::Job::CallCommand`], then services
+    /// `EditorOp` requests the running script makes (cursor reads, selection
+    /// changes, nested `helix.editor.execute_command` calls) by draining them
+    /// against `editor` until the job completes or its deadline trips.
+    pub fn execute_command_on_actor(
+        &mut self,
+        editor: &mut Editor,
+        name: &str,
+        args: Vec<String>,
+    ) -> Result<()> {
+        let actor = self.actor().ok_or_else(|| {
+            PluginError::CommandExecutionFailed(
+                "no EditorCommandRegistry configured for the actor thread".to_string(),
+            )
+        })?;
+
+        actor
+            .submit(actor::Job::CallCommand {
+                name: name.to_string(),
+                args,
+            })
+            .map_err(|_| PluginError::CommandExecutionFailed("actor thread is gone".to_string()))?;
+
+        loop {
+            actor.drain_ops(editor);
+            match actor.try_recv_result() {
+                Some(actor::JobResult::Ok) => return Ok(()),
+                Some(actor::JobResult::Err(e)) => {
+                    return Err(PluginError::CommandExecutionFailed(e))
+                }
+                None => std::thread::sleep(Duration::from_millis(1)),
+            }
+        }
     }
 
     /// Handle a UI/Picker callback from the editor
@@ -161,8 +365,112 @@ impl PluginManager {
         callback_id: u64,
         value: serde_json::Value,
     ) -> Result<()> {
-        let engine = self.engine.read();
-        engine.handle_ui_callback(editor, plugin_name, callback_id, value)
+        for runtime in &self.runtimes {
+            runtime.handle_ui_callback(editor, plugin_name.clone(), callback_id, value.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Load a single plugin directly from `path`, without discovering every
+    /// plugin in the configured directories. Lets a plugin be added while
+    /// the editor is running; its cache entry is written on its own so
+    /// other plugins' cached entries are untouched in memory.
+    pub fn load_plugin_from_path(&mut self, path: std::path::PathBuf) -> Result<()> {
+        let loader = lua::loader::PluginLoader::new(vec![]);
+        let plugin = loader.load_plugin_metadata(&path)?;
+        info!("Loading plugin: {}", plugin.metadata.name);
+        let name = plugin.metadata.name.clone();
+        let cache_entry = loader.cache_entry(&plugin, Vec::new());
+
+        let runtime = self
+            .runtimes
+            .iter_mut()
+            .find(|r| r.handles(&plugin))
+            .ok_or_else(|| {
+                PluginError::InvalidPluginStructure(format!("No runtime can load plugin '{}'", name))
+            })?;
+        runtime.load_plugin(plugin)?;
+
+        if let Some(mut entry) = cache_entry {
+            let cache_dir = Self::cache_dir();
+            let mut cache = lua::cache::PluginCache::load(&cache_dir);
+            entry.commands = runtime.get_commands_for_plugin(&name);
+            if let Err(e) = cache.update(&cache_dir, name, entry) {
+                log::warn!("Failed to write plugin metadata cache entry: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unload `name`, tearing down its event handlers, commands, and
+    /// capability grants so it stops running without restarting the editor.
+    pub fn unload_plugin(&mut self, name: &str) -> Result<()> {
+        let runtime = self
+            .runtimes
+            .iter_mut()
+            .find(|r| r.plugins().iter().any(|p| p.metadata.name == name))
+            .ok_or_else(|| PluginError::PluginNotFound(name.to_string()))?;
+        runtime.unload_plugin(name)?;
+
+        let cache_dir = Self::cache_dir();
+        let mut cache = lua::cache::PluginCache::load(&cache_dir);
+        if let Err(e) = cache.remove(&cache_dir, name) {
+            log::warn!("Failed to write plugin metadata cache: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Reload `name` from disk: unload it, re-read its entry point, and fire
+    /// `OnInit` for just that plugin (not every plugin subscribed to it).
+    pub fn reload_plugin(&mut self, name: &str, editor: &mut Editor) -> Result<()> {
+        let path = self
+            .runtimes
+            .iter()
+            .find_map(|r| r.plugins().into_iter().find(|p| p.metadata.name == name))
+            .map(|plugin| plugin.path)
+            .ok_or_else(|| PluginError::InvalidPluginStructure(format!("Plugin not loaded: {}", name)))?;
+
+        self.unload_plugin(name)?;
+        self.load_plugin_from_path(path)?;
+
+        for runtime in &self.runtimes {
+            runtime.call_event_handlers_for_plugin(
+                editor,
+                &PluginEvent {
+                    event_type: EventType::OnInit,
+                    data: EventData::None,
+                },
+                name,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// List every discoverable plugin alongside whether it's currently
+    /// loaded, for a `:plugins` style status command.
+    pub fn list_plugins(&self) -> Vec<(PluginMetadata, bool)> {
+        let plugin_dirs = if self.config.plugin_dirs.is_empty() {
+            lua::loader::PluginLoader::default_plugin_dirs()
+        } else {
+            self.config.plugin_dirs.clone()
+        };
+
+        let loader = lua::loader::PluginLoader::new(plugin_dirs);
+
+        loader
+            .discover_plugins()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|plugin| {
+                let loaded = self
+                    .runtimes
+                    .iter()
+                    .any(|r| r.plugins().iter().any(|p| p.metadata.name == plugin.metadata.name));
+                (plugin.metadata, loaded)
+            })
+            .collect()
     }
 }
 
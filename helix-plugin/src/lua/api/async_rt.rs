@@ -0,0 +1,70 @@
+use crate::error::Result;
+use crate::lua::scheduler::{self, Driver, PendingAsyncOp};
+use mlua::prelude::*;
+
+/// Register `helix.async`, the plugin-facing entry point to the background
+/// scheduler (see [`crate::lua::scheduler`]).
+pub fn register_async_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
+    let async_module = lua.create_table()?;
+
+    // helix.async.spawn(fn, callback?) - call `fn` right away on the calling
+    // Lua thread (the caller waits until it returns), then hand its result to
+    // the background runtime, which queues `callback` to run on the next
+    // editor tick with it. `fn` itself never runs off the Lua thread.
+    // Returns a `Driver` handle that can `stop()` the task.
+    let spawn = lua.create_function(|lua, (producer, callback): (LuaFunction, Option<LuaFunction>)| {
+        let pending = lua
+            .app_data_ref::<crate::types::PendingAsyncOps>()
+            .ok_or_else(|| LuaError::RuntimeError("async scheduler not initialized".into()))?
+            .0
+            .clone();
+
+        // The producer itself is evaluated on the Lua thread (it is not
+        // `Send`); only its result crosses over to the background runtime so
+        // that the callback is queued without blocking the caller.
+        let value: String = producer.call(())?;
+        let callback_ref = callback.map(|cb| lua.create_registry_value(cb)).transpose()?;
+
+        // Captured now, on the Lua thread, while `_current_plugin_name`
+        // still reflects whichever plugin called `spawn` - by the time the
+        // background task finishes there's no Lua context left to read it
+        // from.
+        let plugin_name = lua
+            .globals()
+            .get::<String>("_current_plugin_name")
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        let runtime = scheduler::background_runtime();
+        let handle = runtime.spawn(async move {
+            if let Some(callback) = callback_ref {
+                pending.lock().push(PendingAsyncOp::Callback {
+                    callback,
+                    result: Ok(value),
+                    plugin_name,
+                });
+            }
+        });
+
+        Ok(Driver::new(handle))
+    })?;
+    async_module.set("spawn", spawn)?;
+
+    helix_table.set("async", async_module)?;
+
+    // helix.timer(ms) - the first awaitable primitive for
+    // `helix.register_command_async` callbacks: suspends the calling
+    // coroutine until `ms` milliseconds pass, then resumes it. Built with
+    // `create_async_function` rather than `helix.async.spawn` because it
+    // needs to suspend *in place* inside the caller's call stack (so editor
+    // APIs called after it still run with the caller's context) instead of
+    // queuing a separate callback for later. `LuaEngine::poll_async` is what
+    // actually drives it forward, one poll per editor tick - this function
+    // only describes the delay, it doesn't block anything itself.
+    let timer = lua.create_async_function(|_, ms: u64| async move {
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        Ok(())
+    })?;
+    helix_table.set("timer", timer)?;
+
+    Ok(())
+}
@@ -2,6 +2,37 @@ use crate::error::Result;
 use helix_core::Position;
 use helix_view::DocumentId;
 use mlua::prelude::*;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Per-buffer callbacks registered through `buffer:on_change`,
+/// `:on_cursor_move`, and `:on_diagnostics`. Dispatched by
+/// [`crate::lua::LuaEngine::fire_buffer_change`] and friends, which the
+/// editor's main loop calls after the corresponding change lands, so plugins
+/// don't have to busy-poll `get_text`/`get_cursor`/`get_diagnostics`.
+#[derive(Default)]
+pub(crate) struct BufferCallbacks {
+    pub on_change: HashMap<DocumentId, Vec<mlua::RegistryKey>>,
+    pub on_cursor_move: HashMap<DocumentId, Vec<mlua::RegistryKey>>,
+    pub on_diagnostics: HashMap<DocumentId, Vec<mlua::RegistryKey>>,
+}
+
+/// Wrapper for [`BufferCallbacks`] stored in Lua app data.
+pub(crate) struct BufferCallbackRegistry(pub Arc<RwLock<BufferCallbacks>>);
+
+/// Find a `ViewId` that can be used to apply a `Transaction` to `document_id`:
+/// prefer a view that actually shows it, and fall back to any open view for a
+/// document with no view (headless apply) since `Document::apply` only needs
+/// a view to thread through per-view jump lists/selection history.
+fn representative_view(editor: &helix_view::Editor, document_id: DocumentId) -> Option<helix_view::ViewId> {
+    editor
+        .tree
+        .views()
+        .find_map(|(view, _)| (view.doc == document_id).then_some(view.id))
+        .or_else(|| editor.tree.views().next().map(|(view, _)| view.id))
+}
 
 /// Lua wrapper for a Helix buffer/document
 #[derive(Clone)]
@@ -80,6 +111,44 @@ impl LuaUserData for LuaBuffer {
         // Get document ID
         methods.add_method("id", |_lua, this, ()| Ok(format!("{:?}", this.document_id)));
 
+        // Get the document's current revision number, for cache invalidation
+        methods.add_method("get_revision", |_lua, this, ()| {
+            let editor = crate::lua::get_editor_mut()?;
+            let doc = editor.document(this.document_id).ok_or_else(|| {
+                LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+            })?;
+            Ok(doc.get_current_revision() as u64)
+        });
+
+        // Fetch a char range of the buffer, xi-editor `get_data` style: callers
+        // pull just the slice they need instead of the whole rope, and can
+        // pass the revision they last saw to detect that it's gone stale.
+        methods.add_method(
+            "get_data",
+            |_lua, this, (offset, max_size, revision): (usize, usize, Option<u64>)| {
+                let editor = crate::lua::get_editor_mut()?;
+                let doc = editor.document(this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+                })?;
+
+                let current_revision = doc.get_current_revision() as u64;
+                if let Some(requested) = revision {
+                    if requested != current_revision {
+                        return Err(LuaError::RuntimeError(format!(
+                            "requested revision {} is stale (current revision is {})",
+                            requested, current_revision
+                        )));
+                    }
+                }
+
+                let text = doc.text();
+                let start = offset.min(text.len_chars());
+                let end = start.saturating_add(max_size).min(text.len_chars());
+
+                Ok((text.slice(start..end).to_string(), current_revision))
+            },
+        );
+
         // Check if buffer is modified
         methods.add_method("is_modified", |_lua, this, ()| {
             let editor = crate::lua::get_editor_mut()?;
@@ -98,19 +167,18 @@ impl LuaUserData for LuaBuffer {
             Ok(doc.language_name().map(|s| s.to_string()))
         });
 
-        // Insert text at position
+        // Insert text at position, in any document regardless of whether
+        // it's shown by the active view.
         methods.add_method(
             "insert",
             |_lua, this, (line, col, text): (usize, usize, String)| {
                 let editor = crate::lua::get_editor_mut()?;
-                let (view, doc) = helix_view::current!(editor);
-
-                // For now, only support current doc
-                if doc.id() != this.document_id {
-                    return Err(LuaError::RuntimeError(
-                        "Modifications currently only supported for the active buffer.".to_string(),
-                    ));
-                }
+                let view_id = representative_view(editor, this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError("No open view available to apply the edit".to_string())
+                })?;
+                let doc = editor.documents.get_mut(&this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+                })?;
 
                 let text_rope = doc.text();
                 let row = (line.saturating_sub(1)).min(text_rope.len_lines().saturating_sub(1));
@@ -122,27 +190,75 @@ impl LuaUserData for LuaBuffer {
                     text_rope,
                     std::iter::once((offset, offset, Some(text.into()))),
                 );
-                doc.apply(&transaction, view.id);
+                doc.apply(&transaction, view_id);
+
+                Ok(())
+            },
+        );
+
+        // Replace a char range without blocking the editor: the replacement
+        // text is resolved on the background runtime (see
+        // `crate::lua::scheduler`) and the resulting `Transaction` is queued
+        // for the next editor tick rather than applied inline, since the
+        // document may have changed by the time the await resolves.
+        methods.add_async_method(
+            "replace_text_async",
+            |lua, this, (start, end, text): (usize, usize, String)| async move {
+                let pending = lua
+                    .app_data_ref::<crate::types::PendingAsyncOps>()
+                    .ok_or_else(|| {
+                        LuaError::RuntimeError("async scheduler not initialized".into())
+                    })?
+                    .0
+                    .clone();
+                let document_id = this.document_id;
+
+                // Stand in for off-thread work (an HTTP fetch, a formatter
+                // subprocess, ...) that produces the replacement text.
+                let resolved = crate::lua::scheduler::background_runtime()
+                    .spawn(async move { text })
+                    .await
+                    .map_err(|e| LuaError::RuntimeError(format!("async task failed: {}", e)))?;
+
+                let editor = crate::lua::get_editor_mut()?;
+                let doc = editor.document(document_id).ok_or_else(|| {
+                    LuaError::RuntimeError(format!("Buffer {:?} no longer exists", document_id))
+                })?;
+                // The document may have shrunk while `text` was resolving;
+                // clamp the caller's range to the current rope length rather
+                // than feeding a stale, now-out-of-bounds range into
+                // `Transaction::change`, which panics on that input.
+                let len = doc.text().len_chars();
+                let start = start.min(len);
+                let end = end.min(len).max(start);
+                let transaction = helix_core::Transaction::change(
+                    doc.text(),
+                    std::iter::once((start, end, Some(resolved.into()))),
+                );
+
+                pending.lock().push(crate::lua::scheduler::PendingAsyncOp::ApplyTransaction {
+                    document_id,
+                    transaction,
+                });
 
                 Ok(())
             },
         );
 
-        // Delete range
+        // Delete range, in any document regardless of whether it's shown by
+        // the active view.
         methods.add_method(
             "delete",
             |_lua,
              this,
              (start_line, start_col, end_line, end_col): (usize, usize, usize, usize)| {
                 let editor = crate::lua::get_editor_mut()?;
-                let (view, doc) = helix_view::current!(editor);
-
-                // For now, only support current doc
-                if doc.id() != this.document_id {
-                    return Err(LuaError::RuntimeError(
-                        "Modifications currently only supported for the active buffer.".to_string(),
-                    ));
-                }
+                let view_id = representative_view(editor, this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError("No open view available to apply the edit".to_string())
+                })?;
+                let doc = editor.documents.get_mut(&this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+                })?;
 
                 let text_rope = doc.text();
 
@@ -160,12 +276,23 @@ impl LuaUserData for LuaBuffer {
                     text_rope,
                     std::iter::once((start_offset, end_offset, None)),
                 );
-                doc.apply(&transaction, view.id);
+                doc.apply(&transaction, view_id);
 
                 Ok(())
             },
         );
 
+        // buffer:edit() - a transaction builder that accumulates several
+        // (start, end, text) changes and commits them as a single
+        // `Transaction`, so they share one undo step and stay consistent
+        // with each other instead of desyncing offsets across calls.
+        methods.add_method("edit", |_lua, this, ()| {
+            Ok(LuaTransactionBuilder {
+                document_id: this.document_id,
+                changes: Vec::new(),
+            })
+        });
+
         // Get selections
         methods.add_method("get_selections", |lua, this, ()| {
             let editor = crate::lua::get_editor_mut()?;
@@ -209,14 +336,12 @@ impl LuaUserData for LuaBuffer {
             "set_annotations",
             |_lua, this, annotations: Vec<LuaPluginAnnotation>| {
                 let editor = crate::lua::get_editor_mut()?;
-                let (view, doc) = helix_view::current!(editor);
-
-                // For now, only support current doc
-                if doc.id() != this.document_id {
-                    return Err(LuaError::RuntimeError(
-                        "Annotations currently only supported for the active buffer.".to_string(),
-                    ));
-                }
+                let view_id = representative_view(editor, this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError("No open view available to set annotations on".to_string())
+                })?;
+                let doc = editor.documents.get_mut(&this.document_id).ok_or_else(|| {
+                    LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+                })?;
 
                 let plugin_annots: Vec<helix_view::document::PluginAnnotation> = annotations
                     .into_iter()
@@ -228,10 +353,13 @@ impl LuaUserData for LuaBuffer {
                         bg: a.bg,
                         offset: a.offset,
                         is_line: a.is_line,
+                        placement: a.placement,
+                        underline: a.underline,
+                        align: a.align,
                     })
                     .collect();
 
-                doc.plugin_annotations.insert(view.id, plugin_annots);
+                doc.plugin_annotations.insert(view_id, plugin_annots);
                 Ok(())
             },
         );
@@ -274,6 +402,80 @@ impl LuaUserData for LuaBuffer {
                 .line_to_char(line_idx.min(doc.text().len_lines())))
         });
 
+        // buffer:on_change(fn) - fn(start_char, end_char, new_text)
+        methods.add_method("on_change", |lua, this, callback: LuaFunction| {
+            let registry = lua
+                .app_data_ref::<BufferCallbackRegistry>()
+                .ok_or_else(|| LuaError::RuntimeError("buffer callback registry not initialized".into()))?;
+            let callback_ref = lua.create_registry_value(callback)?;
+            registry
+                .0
+                .write()
+                .on_change
+                .entry(this.document_id)
+                .or_default()
+                .push(callback_ref);
+            Ok(())
+        });
+
+        // buffer:on_cursor_move(fn) - fn(char_idx)
+        methods.add_method("on_cursor_move", |lua, this, callback: LuaFunction| {
+            let registry = lua
+                .app_data_ref::<BufferCallbackRegistry>()
+                .ok_or_else(|| LuaError::RuntimeError("buffer callback registry not initialized".into()))?;
+            let callback_ref = lua.create_registry_value(callback)?;
+            registry
+                .0
+                .write()
+                .on_cursor_move
+                .entry(this.document_id)
+                .or_default()
+                .push(callback_ref);
+            Ok(())
+        });
+
+        // buffer:on_diagnostics(fn) - fn({ LuaDiagnostic, ... })
+        methods.add_method("on_diagnostics", |lua, this, callback: LuaFunction| {
+            let registry = lua
+                .app_data_ref::<BufferCallbackRegistry>()
+                .ok_or_else(|| LuaError::RuntimeError("buffer callback registry not initialized".into()))?;
+            let callback_ref = lua.create_registry_value(callback)?;
+            registry
+                .0
+                .write()
+                .on_diagnostics
+                .entry(this.document_id)
+                .or_default()
+                .push(callback_ref);
+            Ok(())
+        });
+
+        // Subscribe to remote edits merged in through `helix.workspace` for
+        // this buffer (see `crate::lua::api::workspace`).
+        methods.add_method("on_remote_change", |lua, this, callback: LuaFunction| {
+            let callback_ref = lua.create_registry_value(callback)?;
+            super::workspace::with_workspace_state(lua, |state| {
+                state.register_remote_change(this.document_id, callback_ref)
+            })?;
+            Ok(())
+        });
+
+        // The last known cursor position of each peer editing this buffer,
+        // as `{ {peer = name, cursor = char_idx}, ... }`.
+        methods.add_method("remote_cursors", |lua, this, ()| {
+            let cursors = super::workspace::with_workspace_state(lua, |state| {
+                state.remote_cursors(this.document_id)
+            })?;
+            let table = lua.create_table()?;
+            for (i, (peer, cursor)) in cursors.into_iter().enumerate() {
+                let entry = lua.create_table()?;
+                entry.set("peer", peer)?;
+                entry.set("cursor", cursor)?;
+                table.set(i + 1, entry)?;
+            }
+            Ok(table)
+        });
+
         // Get visual column for char index
         methods.add_method("get_visual_column", |_lua, this, char_idx: usize| {
             let editor = crate::lua::get_editor_mut()?;
@@ -295,6 +497,45 @@ impl LuaUserData for LuaBuffer {
             }
             Ok(column)
         });
+
+        // buffer:snapshot() - a serde-serializable table of the buffer's
+        // full state, suitable for persisting or sending over a wire. See
+        // `helix.buffer.from_snapshot` for the reverse direction and
+        // `helix.json` for a plain JSON string form.
+        methods.add_method("snapshot", |lua, this, ()| {
+            let editor = crate::lua::get_editor_mut()?;
+            let doc = editor.document(this.document_id).ok_or_else(|| {
+                LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+            })?;
+
+            let selections = representative_view(editor, this.document_id)
+                .map(|view_id| {
+                    doc.selection(view_id)
+                        .iter()
+                        .map(|r| SelectionRangeSnapshot {
+                            anchor: r.anchor,
+                            head: r.head,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let snapshot = BufferSnapshot {
+                path: doc.path().map(|p| p.to_string_lossy().to_string()),
+                language: doc.language_name().map(|s| s.to_string()),
+                modified: doc.is_modified(),
+                text: doc.text().to_string(),
+                selections,
+                diagnostics: doc
+                    .diagnostics()
+                    .iter()
+                    .cloned()
+                    .map(LuaDiagnostic::from)
+                    .collect(),
+            };
+
+            lua.to_value(&snapshot)
+        });
     }
 
     fn add_fields<'lua, F: LuaUserDataFields<Self>>(fields: &mut F) {
@@ -305,6 +546,90 @@ impl LuaUserData for LuaBuffer {
     }
 }
 
+/// A single selection range, as carried by [`BufferSnapshot`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SelectionRangeSnapshot {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+/// Serde-serializable snapshot of a buffer's state, round-tripped to/from
+/// plain Lua tables (and JSON, via `helix.json`) by `buffer:snapshot()` and
+/// `helix.buffer.from_snapshot`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BufferSnapshot {
+    pub path: Option<String>,
+    pub language: Option<String>,
+    pub modified: bool,
+    pub text: String,
+    #[serde(default)]
+    pub selections: Vec<SelectionRangeSnapshot>,
+    #[serde(default)]
+    pub diagnostics: Vec<LuaDiagnostic>,
+}
+
+/// Accumulates edits from `buffer:edit()` and commits them as a single
+/// `Transaction` on `:commit()`, so a multi-edit plugin operation gets one
+/// undo step and is applied against a consistent rope snapshot instead of
+/// one transaction (and one rope snapshot) per call.
+pub struct LuaTransactionBuilder {
+    document_id: DocumentId,
+    changes: Vec<(usize, usize, Option<String>)>,
+}
+
+impl LuaUserData for LuaTransactionBuilder {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        // builder:change(start, end, text?) - replace [start, end) with text
+        // (or delete it, if text is omitted)
+        methods.add_method_mut(
+            "change",
+            |_lua, this, (start, end, text): (usize, usize, Option<String>)| {
+                this.changes.push((start, end, text));
+                Ok(())
+            },
+        );
+
+        // builder:commit() - apply all accumulated changes as one Transaction
+        methods.add_method_mut("commit", |_lua, this, ()| {
+            if this.changes.is_empty() {
+                return Ok(());
+            }
+            let editor = crate::lua::get_editor_mut()?;
+            let view_id = representative_view(editor, this.document_id).ok_or_else(|| {
+                LuaError::RuntimeError("No open view available to apply the edit".to_string())
+            })?;
+            let doc = editor.documents.get_mut(&this.document_id).ok_or_else(|| {
+                LuaError::RuntimeError(format!("Buffer {:?} no longer exists", this.document_id))
+            })?;
+
+            let mut changes: Vec<_> = std::mem::take(&mut this.changes)
+                .into_iter()
+                .map(|(start, end, text)| (start, end, text.map(Into::into)))
+                .collect();
+            changes.sort_by_key(|(start, _, _)| *start);
+
+            // `Transaction::change` requires sorted, non-overlapping ranges;
+            // a plugin can hand us overlapping ones (e.g. two `:change()`
+            // calls touching the same span), so reject those here instead of
+            // feeding them through and panicking.
+            for pair in changes.windows(2) {
+                let (_, prev_end, _) = &pair[0];
+                let (next_start, _, _) = &pair[1];
+                if next_start < prev_end {
+                    return Err(LuaError::RuntimeError(format!(
+                        "overlapping changes in transaction: [.., {}) and [{}, ..)",
+                        prev_end, next_start
+                    )));
+                }
+            }
+
+            let transaction = helix_core::Transaction::change(doc.text(), changes.into_iter());
+            doc.apply(&transaction, view_id);
+            Ok(())
+        });
+    }
+}
+
 /// Lua wrapper for a plugin annotation
 #[derive(Clone)]
 pub struct LuaPluginAnnotation {
@@ -315,6 +640,18 @@ pub struct LuaPluginAnnotation {
     pub bg: Option<String>,
     pub offset: u16,
     pub is_line: bool,
+    /// For `is_line` annotations, whether it renders above or below its
+    /// anchored line. Ignored for inline annotations. `"above"` or `"below"`
+    /// (default) from Lua.
+    pub placement: helix_view::annotations::plugins::AnnotationPlacement,
+    /// A `[start_col, end_col)` underline/squiggle span on the anchored
+    /// line, rendered independently of `text`/`style`/`fg`/`bg`. `None`
+    /// means this annotation draws no underline.
+    pub underline: Option<helix_view::annotations::plugins::PluginUnderline>,
+    /// Horizontal placement for an `Above`-placed block - ignored by inline
+    /// and `Below` virtual lines, which always draw from `offset`.
+    /// `"left"` (default), `"center"`, `"right"`, or `"indent"` from Lua.
+    pub align: helix_view::annotations::plugins::AnnotationAlign,
 }
 
 impl LuaUserData for LuaPluginAnnotation {
@@ -326,6 +663,14 @@ impl LuaUserData for LuaPluginAnnotation {
         fields.add_field_method_get("bg", |_lua, this| Ok(this.bg.clone()));
         fields.add_field_method_get("offset", |_lua, this| Ok(this.offset));
         fields.add_field_method_get("is_line", |_lua, this| Ok(this.is_line));
+        fields.add_field_method_get("placement", |_lua, this| Ok(placement_str(this.placement)));
+        fields.add_field_method_get("underline", |lua, this| {
+            this.underline
+                .as_ref()
+                .map(|u| underline_table(lua, u))
+                .transpose()
+        });
+        fields.add_field_method_get("align", |_lua, this| Ok(align_str(this.align)));
     }
 
     fn add_methods<'lua, M: LuaUserDataMethods<Self>>(_methods: &mut M) {}
@@ -342,6 +687,9 @@ impl FromLua for LuaPluginAnnotation {
                 bg: table.get("bg").ok(),
                 offset: table.get("offset").unwrap_or(0),
                 is_line: table.get("is_line").unwrap_or(false),
+                placement: parse_placement(table.get::<Option<String>>("placement").ok().flatten()),
+                underline: parse_underline(table.get::<Option<LuaTable>>("underline").ok().flatten())?,
+                align: parse_align(table.get::<Option<String>>("align").ok().flatten()),
             }),
             LuaValue::UserData(ud) => ud.borrow::<Self>().map(|s| s.clone()),
             _ => Err(LuaError::FromLuaConversionError {
@@ -353,8 +701,85 @@ impl FromLua for LuaPluginAnnotation {
     }
 }
 
+fn parse_placement(value: Option<String>) -> helix_view::annotations::plugins::AnnotationPlacement {
+    match value.as_deref() {
+        Some("above") => helix_view::annotations::plugins::AnnotationPlacement::Above,
+        _ => helix_view::annotations::plugins::AnnotationPlacement::Below,
+    }
+}
+
+fn placement_str(placement: helix_view::annotations::plugins::AnnotationPlacement) -> &'static str {
+    match placement {
+        helix_view::annotations::plugins::AnnotationPlacement::Above => "above",
+        helix_view::annotations::plugins::AnnotationPlacement::Below => "below",
+    }
+}
+
+fn parse_align(value: Option<String>) -> helix_view::annotations::plugins::AnnotationAlign {
+    match value.as_deref() {
+        Some("center") => helix_view::annotations::plugins::AnnotationAlign::Center,
+        Some("right") => helix_view::annotations::plugins::AnnotationAlign::Right,
+        Some("indent") => helix_view::annotations::plugins::AnnotationAlign::Indent,
+        _ => helix_view::annotations::plugins::AnnotationAlign::Left,
+    }
+}
+
+fn align_str(align: helix_view::annotations::plugins::AnnotationAlign) -> &'static str {
+    match align {
+        helix_view::annotations::plugins::AnnotationAlign::Left => "left",
+        helix_view::annotations::plugins::AnnotationAlign::Center => "center",
+        helix_view::annotations::plugins::AnnotationAlign::Right => "right",
+        helix_view::annotations::plugins::AnnotationAlign::Indent => "indent",
+    }
+}
+
+/// Parse `{start_col, end_col, kind = "straight"|"double"|"curly", color}`
+/// into a [`helix_view::annotations::plugins::PluginUnderline`]. `kind`
+/// defaults to `"straight"` when omitted or unrecognized.
+fn parse_underline(
+    table: Option<LuaTable>,
+) -> LuaResult<Option<helix_view::annotations::plugins::PluginUnderline>> {
+    use helix_view::annotations::plugins::{PluginUnderline, UnderlineKind};
+
+    let Some(table) = table else {
+        return Ok(None);
+    };
+    let kind = match table.get::<Option<String>>("kind")?.as_deref() {
+        Some("double") => UnderlineKind::Double,
+        Some("curly") | Some("squiggle") => UnderlineKind::Curly,
+        _ => UnderlineKind::Straight,
+    };
+    Ok(Some(PluginUnderline {
+        start_col: table.get("start_col")?,
+        end_col: table.get("end_col")?,
+        kind,
+        color: table.get("color").ok(),
+    }))
+}
+
+fn underline_table(
+    lua: &Lua,
+    underline: &helix_view::annotations::plugins::PluginUnderline,
+) -> LuaResult<LuaTable> {
+    use helix_view::annotations::plugins::UnderlineKind;
+
+    let table = lua.create_table()?;
+    table.set("start_col", underline.start_col)?;
+    table.set("end_col", underline.end_col)?;
+    table.set(
+        "kind",
+        match underline.kind {
+            UnderlineKind::Straight => "straight",
+            UnderlineKind::Double => "double",
+            UnderlineKind::Curly => "curly",
+        },
+    )?;
+    table.set("color", underline.color.clone())?;
+    Ok(table)
+}
+
 /// Lua wrapper for a text position
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct LuaPosition {
     pub row: usize,
     pub col: usize,
@@ -392,7 +817,7 @@ impl From<LuaPosition> for Position {
 }
 
 /// Lua wrapper for a text range
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct LuaRange {
     pub start: usize,
     pub end: usize,
@@ -412,7 +837,7 @@ impl LuaUserData for LuaRange {
 }
 
 /// Lua wrapper for a diagnostic
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LuaDiagnostic {
     pub range: LuaRange,
     pub line: usize,
@@ -494,10 +919,41 @@ pub fn register_buffer_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
             bg: table.get("bg").ok(),
             offset: table.get("offset").unwrap_or(0),
             is_line: table.get("is_line").unwrap_or(false),
+            placement: parse_placement(table.get::<Option<String>>("placement").ok().flatten()),
+            underline: parse_underline(table.get::<Option<LuaTable>>("underline").ok().flatten())?,
+            align: parse_align(table.get::<Option<String>>("align").ok().flatten()),
         })
     })?;
     buffer_module.set("annotation", annotation)?;
 
+    // helix.buffer.from_snapshot(tbl) - open a new scratch buffer seeded with
+    // a `BufferSnapshot` table (e.g. one produced by `buffer:snapshot()`, or
+    // decoded from JSON via `helix.json`). Only the snapshot's `text` is
+    // applied; its path, language, modified flag, selections and diagnostics
+    // are not restored.
+    let from_snapshot = lua.create_function(|lua, table: LuaValue| {
+        let snapshot: BufferSnapshot = lua.from_value(table)?;
+
+        let editor = crate::lua::get_editor_mut()?;
+        let doc_id = editor.new_file(helix_view::editor::Action::Load);
+        let view_id = representative_view(editor, doc_id).ok_or_else(|| {
+            LuaError::RuntimeError("No open view available to seed the new buffer".to_string())
+        })?;
+        let doc = editor
+            .documents
+            .get_mut(&doc_id)
+            .expect("just created via new_file");
+
+        let transaction = helix_core::Transaction::change(
+            doc.text(),
+            std::iter::once((0, doc.text().len_chars(), Some(snapshot.text.into()))),
+        );
+        doc.apply(&transaction, view_id);
+
+        Ok(LuaBuffer::new(doc_id))
+    })?;
+    buffer_module.set("from_snapshot", from_snapshot)?;
+
     helix_table.set("buffer", buffer_module)?;
 
     Ok(())
@@ -112,59 +112,62 @@ pub fn register_editor_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     })?;
     editor_module.set("set_cursor", set_cursor)?;
 
-    // helix.editor.get_config() - Get editor configuration
+    // helix.editor.get_config() - Get the full editor configuration, serde
+    // round-tripped so every field (not just a hand-picked subset) reaches
+    // Lua as a plain table.
     let get_config = lua.create_function(|lua, ()| {
         let editor = crate::lua::get_editor_mut()?;
-        let config = editor.config();
-        let table = lua.create_table()?;
-
-        table.set("scrolloff", config.scrolloff)?;
-        table.set("mouse", config.mouse)?;
-        table.set("cursorline", config.cursorline)?;
-        table.set("cursorcolumn", config.cursorcolumn)?;
-        table.set("auto_format", config.auto_format)?;
-        table.set("auto_completion", config.auto_completion)?;
-        table.set("auto_info", config.auto_info)?;
-        table.set(
-            "line_number",
-            match config.line_number {
-                helix_view::editor::LineNumber::Absolute => "absolute",
-                helix_view::editor::LineNumber::Relative => "relative",
-            },
-        )?;
-
-        Ok(table)
+        lua.to_value(&editor.config())
     })?;
     editor_module.set("get_config", get_config)?;
 
-    // helix.editor.get_selections() - Get current selections
+    // helix.editor.set_config(table) - Validate a config table against
+    // `helix_view::editor::Config` via serde, then apply it to the running
+    // editor through the same `ConfigEvent::Update` path `:config-reload`
+    // and the config file watcher use, so every other part of the editor
+    // that reacts to a config change (redraw, cursor shape, etc.) sees it
+    // too instead of this API silently diverging from the real reload flow.
+    let set_config = lua.create_function(|lua, value: LuaValue| {
+        let config: helix_view::editor::Config = lua.from_value(value)?;
+        let editor = crate::lua::get_editor_mut()?;
+        editor
+            .config_events
+            .0
+            .send(helix_view::editor::ConfigEvent::Update(Box::new(config)))
+            .map_err(|e| LuaError::RuntimeError(format!("failed to apply config: {}", e)))?;
+        Ok(())
+    })?;
+    editor_module.set("set_config", set_config)?;
+
+    // helix.editor.get_selections() - Get current selections, serde
+    // round-tripped as a list of {anchor, head} tables.
     let get_selections = lua.create_function(|lua, ()| {
         let editor = crate::lua::get_editor_mut()?;
         let (view, doc): (&helix_view::View, &helix_view::Document) =
             helix_view::current_ref!(editor);
-        let selection = doc.selection(view.id);
-        let selections = lua.create_table()?;
-        for (i, range) in selection.iter().enumerate() {
-            let s = lua.create_table()?;
-            s.set("anchor", range.anchor)?;
-            s.set("head", range.head)?;
-            selections.set(i + 1, s)?;
-        }
-        Ok(selections)
+        let snapshots: Vec<super::buffer::SelectionRangeSnapshot> = doc
+            .selection(view.id)
+            .iter()
+            .map(|range| super::buffer::SelectionRangeSnapshot {
+                anchor: range.anchor,
+                head: range.head,
+            })
+            .collect();
+        lua.to_value(&snapshots)
     })?;
     editor_module.set("get_selections", get_selections)?;
 
-    // helix.editor.set_selections(selections) - Set current selections
-    let set_selections = lua.create_function(|_lua, selections: Vec<LuaTable>| {
+    // helix.editor.set_selections(selections) - Set current selections from
+    // a list of {anchor, head} tables, serde round-tripped the same way.
+    let set_selections = lua.create_function(|lua, value: LuaValue| {
+        let snapshots: Vec<super::buffer::SelectionRangeSnapshot> = lua.from_value(value)?;
         let editor = crate::lua::get_editor_mut()?;
         let (view, doc): (&mut helix_view::View, &mut helix_view::Document) =
             helix_view::current!(editor);
-        let mut ranges = Vec::new();
-        for s in selections {
-            let anchor: usize = s.get("anchor")?;
-            let head: usize = s.get("head")?;
-            ranges.push(helix_core::Range::new(anchor, head));
-        }
+        let ranges: Vec<helix_core::Range> = snapshots
+            .into_iter()
+            .map(|s| helix_core::Range::new(s.anchor, s.head))
+            .collect();
         if !ranges.is_empty() {
             let selection = helix_core::Selection::new(ranges.into(), 0);
             doc.set_selection(view.id, selection);
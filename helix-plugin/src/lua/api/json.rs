@@ -0,0 +1,51 @@
+use crate::error::Result;
+use mlua::prelude::*;
+
+/// Register `helix.json`, thin wrappers around mlua's serde support so
+/// plugins can move arbitrary Lua values to/from JSON strings (for RPC-style
+/// plugins, persisting `buffer:snapshot()` output, etc.) without hand-rolling
+/// table-walking code.
+pub fn register_json_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
+    let json_module = lua.create_table()?;
+
+    // helix.json.encode(value) -> string
+    let encode = lua.create_function(|lua, value: LuaValue| {
+        let json_value: serde_json::Value = lua.from_value(value)?;
+        serde_json::to_string(&json_value)
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to encode JSON: {}", e)))
+    })?;
+    json_module.set("encode", encode)?;
+
+    // helix.json.decode(string) -> value
+    let decode = lua.create_function(|lua, text: String| {
+        let json_value: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to decode JSON: {}", e)))?;
+        lua.to_value(&json_value)
+    })?;
+    json_module.set("decode", decode)?;
+
+    helix_table.set("json", json_module)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_lua() {
+        let lua = Lua::new();
+        let helix_table = lua.create_table().unwrap();
+        register_json_api(&lua, &helix_table).unwrap();
+        lua.globals().set("helix", helix_table).unwrap();
+
+        let result: String = lua
+            .load(r#"return helix.json.encode(helix.json.decode('{"a":1,"b":[true,false]}'))"#)
+            .eval()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["a"], 1);
+        assert_eq!(value["b"][0], true);
+    }
+}
@@ -6,6 +6,7 @@ pub fn register_lsp_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
 
     // helix.lsp.get_clients() - Get active LSP clients for current buffer
     let get_clients = lua.create_function(|lua, ()| {
+        crate::types::require_capability(lua, crate::types::Capability::Lsp)?;
         let editor = crate::lua::get_editor_mut()?;
         let (_, _doc) = helix_view::current_ref!(editor);
 
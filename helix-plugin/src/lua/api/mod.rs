@@ -2,17 +2,25 @@
 ///
 /// This module contains all the Rust-Lua bridge code that exposes
 /// Helix functionality to Lua plugins.
+pub mod async_rt;
 pub mod buffer;
 pub mod editor;
+pub mod json;
 pub mod log;
 pub mod lsp;
+pub mod storage;
 pub mod ui;
 pub mod window;
+pub mod workspace;
 
 // Re-exports for convenience
+pub use async_rt::*;
 pub use buffer::*;
 pub use editor::*;
+pub use json::*;
 pub use log::*;
 pub use lsp::*;
+pub use storage::*;
 pub use ui::*;
 pub use window::*;
+pub use workspace::LuaWorkspace;
@@ -0,0 +1,104 @@
+use crate::error::Result;
+use mlua::prelude::*;
+use std::sync::OnceLock;
+
+static DB: OnceLock<sled::Db> = OnceLock::new();
+
+fn database() -> LuaResult<&'static sled::Db> {
+    if let Some(db) = DB.get() {
+        return Ok(db);
+    }
+    let path = helix_loader::config_dir().join("plugin-storage");
+    let db = sled::open(&path)
+        .map_err(|e| LuaError::RuntimeError(format!("Failed to open plugin storage at {:?}: {}", path, e)))?;
+    Ok(DB.get_or_init(|| db))
+}
+
+/// Each plugin's namespace is a sled `Tree` derived from its name, so
+/// plugins can't read (or clobber) each other's persisted data.
+fn plugin_tree(lua: &Lua) -> LuaResult<sled::Tree> {
+    crate::types::require_capability(lua, crate::types::Capability::Storage)?;
+
+    let plugin_name = lua
+        .globals()
+        .get::<String>("_current_plugin_name")
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    // `_current_plugin_name` is only "unknown" if something called into
+    // storage without an active plugin context (`LuaEngine::with_plugin_context`
+    // re-establishes it around command, event-handler, UI and async
+    // callbacks; `buffer:on_change`/`on_cursor_move`/`on_diagnostics`
+    // callbacks run without it, so they land here). Refuse instead of
+    // quietly opening a shared "unknown" tree every such caller would collide
+    // on - that would collapse the whole point of per-plugin namespacing.
+    if plugin_name == "unknown" {
+        return Err(LuaError::RuntimeError(
+            "helix.storage called with no active plugin context".to_string(),
+        ));
+    }
+
+    database()?
+        .open_tree(&plugin_name)
+        .map_err(|e| LuaError::RuntimeError(format!("Failed to open storage tree '{}': {}", plugin_name, e)))
+}
+
+/// Register `helix.storage`, a namespaced, durable key-value store backed by
+/// an embedded `sled` database so plugins can persist state (bookmarks,
+/// project settings, usage stats) across editor restarts.
+pub fn register_storage_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
+    let storage_module = lua.create_table()?;
+
+    // helix.storage.set(key, value)
+    let set = lua.create_function(|lua, (key, value): (String, LuaValue)| {
+        let json: serde_json::Value = lua.from_value(value)?;
+        let bytes = serde_json::to_vec(&json)
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to serialize value: {}", e)))?;
+        plugin_tree(lua)?
+            .insert(key.as_bytes(), bytes)
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to write to storage: {}", e)))?;
+        Ok(())
+    })?;
+    storage_module.set("set", set)?;
+
+    // helix.storage.get(key)
+    let get = lua.create_function(|lua, key: String| {
+        let bytes = plugin_tree(lua)?
+            .get(key.as_bytes())
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to read storage: {}", e)))?;
+        match bytes {
+            Some(bytes) => {
+                let json: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| {
+                    LuaError::RuntimeError(format!("Failed to deserialize stored value: {}", e))
+                })?;
+                lua.to_value(&json)
+            }
+            None => Ok(LuaValue::Nil),
+        }
+    })?;
+    storage_module.set("get", get)?;
+
+    // helix.storage.delete(key)
+    let delete = lua.create_function(|lua, key: String| {
+        plugin_tree(lua)?
+            .remove(key.as_bytes())
+            .map_err(|e| LuaError::RuntimeError(format!("Failed to delete from storage: {}", e)))?;
+        Ok(())
+    })?;
+    storage_module.set("delete", delete)?;
+
+    // helix.storage.keys()
+    let keys = lua.create_function(|lua, ()| {
+        let table = lua.create_table()?;
+        for (i, key) in plugin_tree(lua)?.iter().keys().enumerate() {
+            let key =
+                key.map_err(|e| LuaError::RuntimeError(format!("Failed to iterate storage: {}", e)))?;
+            table.set(i + 1, String::from_utf8_lossy(&key).to_string())?;
+        }
+        Ok(table)
+    })?;
+    storage_module.set("keys", keys)?;
+
+    helix_table.set("storage", storage_module)?;
+
+    Ok(())
+}
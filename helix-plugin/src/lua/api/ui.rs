@@ -1,14 +1,382 @@
 use crate::error::Result;
+use crate::types::{
+    CachedPanel, DockSide, PanelConstraint, PanelDirection, PanelNode, PanelWidgetType,
+    PickerItem, PopoverAnchor, ResolvedPanelWidget, ResolvedPopover,
+};
+use helix_core::doc_formatter::TextFormat;
+use helix_core::softwrapped_dimensions;
+use helix_view::annotations::ansi;
+use helix_view::graphics::Rect;
 use mlua::prelude::*;
 
+/// Parse `helix.ui.picker`'s `items` table into [`PickerItem`] rows. Each
+/// entry is either a plain string (used as both id and label) or a table
+/// `{id, text, secondary, columns, data}`. Display fields have ANSI SGR
+/// escapes stripped - like `helix.ui.notify`, the picker doesn't render
+/// styled spans yet, so a plugin piping in colored tool output would
+/// otherwise show raw escape bytes instead of plain text. `data` is kept as
+/// whatever Lua value the plugin passed (converted to JSON) and handed back
+/// verbatim on selection/preview/filter, independent of `id`/`columns`.
+fn parse_picker_items(lua: &Lua, items_table: LuaTable) -> LuaResult<Vec<PickerItem>> {
+    let mut items = Vec::new();
+    for value in items_table.sequence_values::<LuaValue>() {
+        let item = match value? {
+            LuaValue::String(s) => {
+                let text = ansi::strip_ansi(&s.to_string_lossy());
+                PickerItem {
+                    id: text.clone(),
+                    label: text,
+                    secondary: None,
+                    columns: Vec::new(),
+                    data: serde_json::Value::Null,
+                }
+            }
+            LuaValue::Table(row) => {
+                let label: String = row
+                    .get::<Option<String>>("text")?
+                    .or(row.get::<Option<String>>("label")?)
+                    .map(|s| ansi::strip_ansi(&s))
+                    .unwrap_or_default();
+                let data = match row.get::<Option<LuaValue>>("data")? {
+                    Some(value) => lua.from_value(value).unwrap_or(serde_json::Value::Null),
+                    None => serde_json::Value::Null,
+                };
+                PickerItem {
+                    id: row
+                        .get::<Option<String>>("id")?
+                        .unwrap_or_else(|| label.clone()),
+                    label,
+                    secondary: row
+                        .get::<Option<String>>("secondary")
+                        .ok()
+                        .flatten()
+                        .map(|s| ansi::strip_ansi(&s)),
+                    columns: row
+                        .get::<Option<Vec<String>>>("columns")?
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|c| ansi::strip_ansi(c))
+                        .collect(),
+                    data,
+                }
+            }
+            _ => {
+                return Err(LuaError::RuntimeError(
+                    "picker items must be strings or {id, text, secondary, columns, data} tables"
+                        .to_string(),
+                ))
+            }
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Split `area` along `direction` into one sub-rect per entry in
+/// `constraints`. `Length`/`Percentage` entries are sized first; whatever
+/// space is left over is shared evenly among `Min` entries (each still gets
+/// at least its minimum, space permitting). This is a simplified stand-in
+/// for ratatui's constraint solver - this crate doesn't depend on ratatui,
+/// so it only needs to cover the three constraint kinds `create_panel`
+/// exposes, not ratatui's full `Layout`.
+fn split_rect(
+    area: Rect,
+    direction: PanelDirection,
+    constraints: &[PanelConstraint],
+) -> Vec<Rect> {
+    let total = match direction {
+        PanelDirection::Horizontal => area.width,
+        PanelDirection::Vertical => area.height,
+    };
+
+    let mut sizes = vec![0u16; constraints.len()];
+    let mut used = 0u16;
+    for (i, constraint) in constraints.iter().enumerate() {
+        sizes[i] = match constraint {
+            PanelConstraint::Length(n) => *n,
+            PanelConstraint::Percentage(p) => ((total as u32 * *p as u32) / 100) as u16,
+            PanelConstraint::Min(_) => 0,
+        };
+        used = used.saturating_add(sizes[i]).min(total);
+    }
+
+    let remaining = total.saturating_sub(used);
+    let min_indices: Vec<usize> = constraints
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| matches!(c, PanelConstraint::Min(_)))
+        .map(|(i, _)| i)
+        .collect();
+    if !min_indices.is_empty() {
+        let share = remaining / min_indices.len() as u16;
+        let mut extra = remaining % min_indices.len() as u16;
+        let mut left = remaining;
+        for i in min_indices {
+            let PanelConstraint::Min(min) = constraints[i] else {
+                unreachable!()
+            };
+            sizes[i] = share.max(min);
+            if extra > 0 {
+                sizes[i] = sizes[i].saturating_add(1);
+                extra -= 1;
+            }
+            sizes[i] = sizes[i].min(left);
+            left -= sizes[i];
+        }
+    }
+
+    let mut rects = Vec::with_capacity(constraints.len());
+    let mut offset = 0u16;
+    for size in sizes {
+        let rect = match direction {
+            PanelDirection::Horizontal => Rect {
+                x: area.x + offset,
+                y: area.y,
+                width: size,
+                height: area.height,
+            },
+            PanelDirection::Vertical => Rect {
+                x: area.x,
+                y: area.y + offset,
+                width: area.width,
+                height: size,
+            },
+        };
+        rects.push(rect);
+        offset = offset.saturating_add(size);
+    }
+    rects
+}
+
+/// Carve a `size`-wide (left/right) or `size`-tall (bottom) strip off
+/// `area`'s edge for a docked panel.
+pub(crate) fn dock_rect(area: Rect, dock: DockSide, size: u16) -> Rect {
+    match dock {
+        DockSide::Left => Rect {
+            x: area.x,
+            y: area.y,
+            width: size.min(area.width),
+            height: area.height,
+        },
+        DockSide::Right => Rect {
+            x: area.x + area.width.saturating_sub(size),
+            y: area.y,
+            width: size.min(area.width),
+            height: area.height,
+        },
+        DockSide::Bottom => Rect {
+            x: area.x,
+            y: area.y + area.height.saturating_sub(size),
+            width: area.width,
+            height: size.min(area.height),
+        },
+    }
+}
+
+/// Parse a `helix.ui.create_panel` layout node: a `split` table with
+/// `direction` + `children` (each `{constraint = {...}, node = {...}}`), or
+/// a `widget` leaf table with `widget` ("list"|"table"|"paragraph") and a
+/// `render` callback. The callback is stashed in the Lua registry
+/// immediately so the parsed tree can outlive this call (cached in
+/// [`crate::types::PanelRegistry`] for `redraw_panels`).
+fn parse_panel_node(lua: &Lua, node: &LuaTable) -> LuaResult<PanelNode> {
+    if let Some(direction) = node.get::<Option<String>>("direction")? {
+        let direction = match direction.as_str() {
+            "horizontal" => PanelDirection::Horizontal,
+            "vertical" => PanelDirection::Vertical,
+            other => {
+                return Err(LuaError::RuntimeError(format!(
+                    "unknown split direction '{}', expected \"horizontal\" or \"vertical\"",
+                    other
+                )))
+            }
+        };
+
+        let children_table: LuaTable = node
+            .get("children")
+            .map_err(|_| LuaError::RuntimeError("split node requires 'children'".into()))?;
+
+        let mut children = Vec::new();
+        for entry in children_table.sequence_values::<LuaTable>() {
+            let entry = entry?;
+            let constraint_table: LuaTable = entry
+                .get("constraint")
+                .map_err(|_| LuaError::RuntimeError("split child requires 'constraint'".into()))?;
+            let constraint = parse_constraint(&constraint_table)?;
+            let child_table: LuaTable = entry
+                .get("node")
+                .map_err(|_| LuaError::RuntimeError("split child requires 'node'".into()))?;
+            children.push((constraint, parse_panel_node(lua, &child_table)?));
+        }
+
+        Ok(PanelNode::Split { direction, children })
+    } else if let Some(widget) = node.get::<Option<String>>("widget")? {
+        let widget_type = match widget.as_str() {
+            "list" => PanelWidgetType::List,
+            "table" => PanelWidgetType::Table,
+            "paragraph" => PanelWidgetType::Paragraph,
+            other => {
+                return Err(LuaError::RuntimeError(format!(
+                    "unknown widget type '{}', expected \"list\", \"table\", or \"paragraph\"",
+                    other
+                )))
+            }
+        };
+        let render: LuaFunction = node
+            .get("render")
+            .map_err(|_| {
+                LuaError::RuntimeError("widget node requires a 'render' function".into())
+            })?;
+        let render = lua.create_registry_value(render)?;
+
+        Ok(PanelNode::Widget {
+            widget_type,
+            render,
+        })
+    } else {
+        Err(LuaError::RuntimeError(
+            "panel node must have 'direction' (a split) or 'widget' (a leaf)".into(),
+        ))
+    }
+}
+
+fn parse_constraint(table: &LuaTable) -> LuaResult<PanelConstraint> {
+    if let Some(p) = table.get::<Option<u16>>("percentage")? {
+        return Ok(PanelConstraint::Percentage(p));
+    }
+    if let Some(l) = table.get::<Option<u16>>("length")? {
+        return Ok(PanelConstraint::Length(l));
+    }
+    if let Some(m) = table.get::<Option<u16>>("min")? {
+        return Ok(PanelConstraint::Min(m));
+    }
+    Err(LuaError::RuntimeError(
+        "constraint must be {percentage=N}, {length=N}, or {min=N}".into(),
+    ))
+}
+
+/// Walk `node`, splitting `area` at each `Split` and calling every
+/// `Widget` leaf's `render` callback, collecting the resolved
+/// `(area, widget_type, content)` for each leaf in layout order.
+pub(crate) fn render_panel(
+    lua: &Lua,
+    area: Rect,
+    node: &PanelNode,
+) -> LuaResult<Vec<ResolvedPanelWidget>> {
+    let mut widgets = Vec::new();
+    render_panel_into(lua, area, node, &mut widgets)?;
+    Ok(widgets)
+}
+
+fn render_panel_into(
+    lua: &Lua,
+    area: Rect,
+    node: &PanelNode,
+    out: &mut Vec<ResolvedPanelWidget>,
+) -> LuaResult<()> {
+    match node {
+        PanelNode::Split { direction, children } => {
+            let constraints: Vec<PanelConstraint> = children.iter().map(|(c, _)| *c).collect();
+            let areas = split_rect(area, *direction, &constraints);
+            for (child_area, (_, child)) in areas.into_iter().zip(children.iter()) {
+                render_panel_into(lua, child_area, child, out)?;
+            }
+        }
+        PanelNode::Widget {
+            widget_type,
+            render,
+        } => {
+            let callback: LuaFunction = lua.registry_value(render)?;
+            let result: LuaValue = callback.call(())?;
+            let content = lua.from_value(result).unwrap_or(serde_json::Value::Null);
+            out.push(ResolvedPanelWidget {
+                area,
+                widget_type: *widget_type,
+                content,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Soft-wrapped row height of one content line at `width` columns, using
+/// the same `TextFormat` + `softwrapped_dimensions` path `PluginLineAnnotations`
+/// uses for virtual-line sizing.
+fn wrapped_line_height(text: &str, width: u16, tab_width: u16) -> u16 {
+    let text_fmt = TextFormat {
+        soft_wrap: true,
+        tab_width,
+        max_wrap: width.saturating_div(4).max(20),
+        max_indent_retain: 0,
+        wrap_indicator_highlight: None,
+        viewport_width: width,
+        soft_wrap_at_text_width: true,
+    };
+    softwrapped_dimensions(text.into(), &text_fmt).0 as u16
+}
+
+/// Place a `helix.ui.popover` box for `content` (ANSI escapes already
+/// stripped) anchored at `anchor_pos` in `viewport`. Prefers opening below
+/// the anchor; falls back above it when there isn't enough room below,
+/// picking whichever side has more space when neither fully fits. Width is
+/// `max_width` clamped to the viewport, then clamped horizontally so the
+/// box never runs past the right edge; height is the wrapped content
+/// height, capped at `max_height` when `scrollable` (and otherwise at
+/// whichever side's available rows it was placed into).
+fn place_popover(
+    viewport: Rect,
+    anchor_pos: helix_core::Position,
+    content: &[String],
+    max_width: u16,
+    max_height: Option<u16>,
+    scrollable: bool,
+    tab_width: u16,
+) -> Rect {
+    let width = max_width.min(viewport.width.saturating_sub(2)).max(10);
+    let content_height: u16 = content
+        .iter()
+        .map(|line| wrapped_line_height(line, width, tab_width))
+        .sum::<u16>()
+        .max(1);
+
+    let anchor_row = anchor_pos.row as u16;
+    let rows_below = viewport.height.saturating_sub(anchor_row + 1);
+    let rows_above = anchor_row;
+
+    let below = rows_below >= content_height || rows_below >= rows_above;
+    let available = if below { rows_below } else { rows_above };
+    let height = match max_height {
+        Some(h) if scrollable => content_height.min(h),
+        _ => content_height.min(available.max(1)),
+    };
+
+    let y = if below {
+        anchor_row + 1
+    } else {
+        anchor_row.saturating_sub(height)
+    };
+    let x = (anchor_pos.col as u16).min(viewport.width.saturating_sub(width));
+
+    Rect {
+        x: viewport.x + x,
+        y: viewport.y + y,
+        width,
+        height,
+    }
+}
+
 /// Register UI API in the Helix Lua global table
 pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     let ui_module = lua.create_table()?;
 
-    // helix.ui.notify(message, level) - Show notification
+    // helix.ui.notify(message, level) - Show notification. The status line
+    // can't render styled spans yet, so any ANSI SGR codes a plugin passes
+    // through (e.g. forwarded test-runner output) are stripped rather than
+    // shown as raw escape bytes - see `helix_view::annotations::ansi`,
+    // which annotations use for the real thing.
     let notify = lua.create_function(|_lua, (message, _level): (String, Option<String>)| {
         if let Ok(editor) = crate::lua::get_editor_mut() {
-            editor.set_status(message);
+            editor.set_status(ansi::strip_ansi(&message));
         }
         Ok(())
     })?;
@@ -17,7 +385,7 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     // helix.ui.info(message) - Show info message
     let info = lua.create_function(|_lua, message: String| {
         if let Ok(editor) = crate::lua::get_editor_mut() {
-            editor.set_status(message);
+            editor.set_status(ansi::strip_ansi(&message));
         }
         Ok(())
     })?;
@@ -26,7 +394,7 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     // helix.ui.warn(message) - Show warning message
     let warn = lua.create_function(|_lua, message: String| {
         if let Ok(editor) = crate::lua::get_editor_mut() {
-            editor.set_status(format!("Warning: {}", message));
+            editor.set_status(format!("Warning: {}", ansi::strip_ansi(&message)));
         }
         Ok(())
     })?;
@@ -35,7 +403,7 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     // helix.ui.error(message) - Show error message
     let error = lua.create_function(|_lua, message: String| {
         if let Ok(editor) = crate::lua::get_editor_mut() {
-            editor.set_error(message);
+            editor.set_error(ansi::strip_ansi(&message));
         }
         Ok(())
     })?;
@@ -125,7 +493,18 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     })?;
     ui_module.set("confirm", confirm)?;
 
-    // helix.ui.picker(options) - Show picker/menu
+    // helix.ui.picker(options) - Show a fuzzy-matching picker. `items` may
+    // be plain strings, or tables `{id, text, secondary, columns, data}` for
+    // Helix's richer column-picker layout; `data` is handed back verbatim to
+    // `on_select`/`on_preview`/`on_filter` instead of just the display
+    // string. `column_names` (a header row) and `column_widths` (per-column
+    // width constraints, 0 = size to content) only matter when items carry
+    // `columns`. `filter_column` picks which column (0-based) the built-in
+    // fuzzy matcher runs against (default: `label`); `on_filter(query, item)`
+    // overrides the built-in matcher entirely, returning true/false per item.
+    // `multi_select = true` delivers `on_select` a list of items instead of a
+    // single one; `on_preview`, if given, is called with the highlighted
+    // item as the selection moves.
     let picker = lua.create_function(|lua, options: LuaTable| {
         let editor = match crate::lua::get_editor_mut() {
             Ok(e) => e,
@@ -152,32 +531,143 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
         };
 
         // Extract picker options
-        let items: Vec<String> = options
-            .get::<Option<Vec<String>>>("items")?
-            .unwrap_or_default();
+        let items = match options.get::<Option<LuaTable>>("items")? {
+            Some(items_table) => parse_picker_items(lua, items_table)?,
+            None => Vec::new(),
+        };
         let prompt_text: String = options
             .get::<Option<String>>("prompt")?
             .unwrap_or_else(|| "Select:".to_string());
+        let multi_select: bool = options.get::<Option<bool>>("multi_select")?.unwrap_or(false);
+        let column_names: Vec<String> = options
+            .get::<Option<Vec<String>>>("column_names")?
+            .unwrap_or_default();
+        let column_widths: Vec<u16> = options
+            .get::<Option<Vec<u16>>>("column_widths")?
+            .unwrap_or_default();
+        let filter_column: Option<usize> = options.get("filter_column").ok();
         let callback: LuaFunction = options
             .get("on_select")
             .map_err(|_| LuaError::RuntimeError("on_select callback required".into()))?;
 
         let callback_id = counter.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
         let callback_ref = lua.create_registry_value(callback)?;
-
         callback_reg
             .0
             .write()
             .insert((plugin_name.clone(), callback_id), callback_ref);
 
-        handler
-            .0
-            .picker(editor, items, prompt_text, plugin_name, callback_id);
+        let preview_callback_id = match options.get::<Option<LuaFunction>>("on_preview")? {
+            Some(preview) => {
+                let preview_id = counter.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let preview_ref = lua.create_registry_value(preview)?;
+                callback_reg
+                    .0
+                    .write()
+                    .insert((plugin_name.clone(), preview_id), preview_ref);
+                Some(preview_id)
+            }
+            None => None,
+        };
+
+        let filter_callback_id = match options.get::<Option<LuaFunction>>("on_filter")? {
+            Some(filter) => {
+                let filter_id = counter.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let filter_ref = lua.create_registry_value(filter)?;
+                callback_reg
+                    .0
+                    .write()
+                    .insert((plugin_name.clone(), filter_id), filter_ref);
+                Some(filter_id)
+            }
+            None => None,
+        };
+
+        let picker_options = crate::types::PickerOptions {
+            prompt: prompt_text,
+            multi_select,
+            column_names,
+            column_widths,
+            filter_column,
+            filter_callback_id,
+            preview_callback_id,
+        };
+
+        handler.0.picker(editor, items, picker_options, plugin_name, callback_id);
 
         Ok(())
     })?;
     ui_module.set("picker", picker)?;
 
+    // helix.ui.inline_input(options) - Show an editable input field anchored
+    // over a buffer range (the current selection by default), rather than
+    // a bottom-of-screen prompt. `on_submit(text, start, end)` receives the
+    // typed text plus the anchor range it was shown against.
+    let inline_input = lua.create_function(|lua, options: LuaTable| {
+        let editor = match crate::lua::get_editor_mut() {
+            Ok(e) => e,
+            Err(_) => return Ok(()),
+        };
+        let plugin_name = lua
+            .globals()
+            .get::<String>("_current_plugin_name")
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        let handler = match lua.app_data_ref::<crate::types::UiHandlerWrapper>() {
+            Some(h) => h,
+            None => return Ok(()),
+        };
+        let callback_reg = match lua.app_data_ref::<crate::types::UiCallbackRegistry>() {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        let counter = match lua.app_data_ref::<crate::types::UiCallbackCounter>() {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+
+        let (view, doc): (&helix_view::View, &helix_view::Document) =
+            helix_view::current_ref!(editor);
+        let primary = doc.selection(view.id).primary();
+        let document_id = doc.id();
+
+        let start: usize = options
+            .get::<Option<usize>>("start")?
+            .unwrap_or(primary.from());
+        let end: usize = options.get::<Option<usize>>("end")?.unwrap_or(primary.to());
+        let prompt_text: String = options
+            .get::<Option<String>>("prompt")?
+            .unwrap_or_else(|| "Input:".to_string());
+        let default_text: Option<String> = options.get("default").ok();
+        let callback: LuaFunction = options
+            .get("on_submit")
+            .map_err(|_| LuaError::RuntimeError("on_submit callback required".into()))?;
+
+        let callback_id = counter.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let callback_ref = lua.create_registry_value(callback)?;
+        callback_reg
+            .0
+            .write()
+            .insert((plugin_name.clone(), callback_id), callback_ref);
+
+        let anchor = crate::types::InlineInputAnchor {
+            document_id,
+            start,
+            end,
+        };
+        handler.0.inline_input(
+            editor,
+            anchor,
+            prompt_text,
+            default_text,
+            plugin_name,
+            callback_id,
+        );
+
+        Ok(())
+    })?;
+    ui_module.set("inline_input", inline_input)?;
+
     // helix.ui.menu(items, callback) - Show menu
     let menu = lua.create_function(
         |_lua, (items, _callback): (Vec<String>, Option<LuaFunction>)| {
@@ -244,6 +734,154 @@ pub fn register_ui_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
     })?;
     ui_module.set("show_help", show_help)?;
 
+    // helix.ui.create_panel(options) - Declare a docked, persistent panel.
+    // `options` is `{dock = "left"|"right"|"bottom", size = N, layout =
+    // <panel node>}`. The layout is resolved against the docked area right
+    // away and cached (keyed by plugin name) in [`crate::types::PanelRegistry`]
+    // so `LuaEngine::redraw_panels` can re-invoke the `render` callbacks
+    // without re-parsing `layout` on every redraw.
+    let create_panel = lua.create_function(|lua, options: LuaTable| {
+        let editor = match crate::lua::get_editor_mut() {
+            Ok(e) => e,
+            Err(_) => return Ok(()),
+        };
+        let plugin_name = lua
+            .globals()
+            .get::<String>("_current_plugin_name")
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        let handler = match lua.app_data_ref::<crate::types::UiHandlerWrapper>() {
+            Some(h) => h,
+            None => return Ok(()),
+        };
+
+        let registry = match lua.app_data_ref::<crate::types::PanelRegistry>() {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        let dock_name: String = options
+            .get("dock")
+            .map_err(|_| LuaError::RuntimeError("create_panel requires a 'dock' side".into()))?;
+        let dock = match dock_name.as_str() {
+            "left" => DockSide::Left,
+            "right" => DockSide::Right,
+            "bottom" => DockSide::Bottom,
+            other => {
+                return Err(LuaError::RuntimeError(format!(
+                    "unknown dock side '{}', expected \"left\", \"right\", or \"bottom\"",
+                    other
+                )))
+            }
+        };
+        let size: u16 = options
+            .get("size")
+            .map_err(|_| LuaError::RuntimeError("create_panel requires a 'size'".into()))?;
+        let layout_table: LuaTable = options
+            .get("layout")
+            .map_err(|_| LuaError::RuntimeError("create_panel requires a 'layout' node".into()))?;
+        let layout = parse_panel_node(lua, &layout_table)?;
+
+        let area = dock_rect(editor.tree.area(), dock, size);
+        let widgets = render_panel(lua, area, &layout)?;
+
+        handler.0.create_panel(editor, plugin_name.clone(), dock, widgets.clone());
+
+        registry.0.write().insert(
+            plugin_name,
+            CachedPanel {
+                dock,
+                size,
+                layout,
+                widgets,
+            },
+        );
+
+        Ok(())
+    })?;
+    ui_module.set("create_panel", create_panel)?;
+
+    // helix.ui.popover(char_idx, content, opts) - Show a transient floating
+    // box anchored to a buffer position: hover docs, signature help, or
+    // plugin diagnostics that don't fit in the status line or a line
+    // annotation. `content` is a list of strings, each of which may carry
+    // ANSI SGR escapes (parsed with `helix_view::annotations::ansi`, same as
+    // `PluginDecoration` uses for styled annotation text). Placement prefers
+    // below the anchor, falling back above it when there isn't room, and is
+    // clamped to stay inside `editor.tree.area()`. `opts.max_width` caps how
+    // wide the box wraps (default 60, still clamped to the viewport);
+    // `opts.scrollable = true` caps the height at `opts.max_height` instead
+    // of growing to fit every line - the scrolling interaction itself is up
+    // to the `UiHandler` impl.
+    let popover = lua.create_function(
+        |lua, (char_idx, content, opts): (usize, Vec<String>, Option<LuaTable>)| {
+            let editor = match crate::lua::get_editor_mut() {
+                Ok(e) => e,
+                Err(_) => return Ok(()),
+            };
+
+            let handler = match lua.app_data_ref::<crate::types::UiHandlerWrapper>() {
+                Some(h) => h,
+                None => return Ok(()),
+            };
+
+            let plugin_name = lua
+                .globals()
+                .get::<String>("_current_plugin_name")
+                .unwrap_or_else(|_| "unknown".to_string());
+
+            let max_width: u16 = opts
+                .as_ref()
+                .and_then(|o| o.get::<Option<u16>>("max_width").ok().flatten())
+                .unwrap_or(60);
+            let max_height: Option<u16> = opts
+                .as_ref()
+                .and_then(|o| o.get::<Option<u16>>("max_height").ok().flatten());
+            let scrollable: bool = opts
+                .as_ref()
+                .and_then(|o| o.get::<Option<bool>>("scrollable").ok().flatten())
+                .unwrap_or(false);
+
+            let (view, doc): (&helix_view::View, &helix_view::Document) =
+                helix_view::current_ref!(editor);
+            let document_id = doc.id();
+            let char_idx = char_idx.min(doc.text().len_chars());
+            let tab_width = doc.tab_width() as u16;
+            let anchor_pos = view
+                .screen_coords_at_pos(doc, doc.text().slice(..), char_idx)
+                .unwrap_or_else(|| helix_core::Position::new(0, 0));
+            let viewport = editor.tree.area();
+
+            let plain_lines: Vec<String> = content.iter().map(|l| ansi::strip_ansi(l)).collect();
+            let area = place_popover(
+                viewport,
+                anchor_pos,
+                &plain_lines,
+                max_width,
+                max_height,
+                scrollable,
+                tab_width,
+            );
+            let lines: Vec<crate::types::PopoverLine> =
+                content.iter().map(|line| ansi::parse_ansi(line)).collect();
+
+            let resolved = ResolvedPopover {
+                anchor: PopoverAnchor {
+                    document_id,
+                    char_idx,
+                },
+                lines,
+                area,
+                scrollable,
+            };
+
+            handler.0.popover(editor, plugin_name, resolved);
+
+            Ok(())
+        },
+    )?;
+    ui_module.set("popover", popover)?;
+
     helix_table.set("ui", ui_module)?;
 
     Ok(())
@@ -269,6 +907,7 @@ mod tests {
         assert!(ui_module.contains_key("error").unwrap());
         assert!(ui_module.contains_key("prompt").unwrap());
         assert!(ui_module.contains_key("picker").unwrap());
+        assert!(ui_module.contains_key("inline_input").unwrap());
         assert!(ui_module.contains_key("menu").unwrap());
     }
 
@@ -0,0 +1,405 @@
+use crate::crdt::{DeleteOp, InsertOp, RgaSequence};
+use crate::error::Result;
+use helix_view::{DocumentId, Editor};
+use mlua::prelude::*;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// A CRDT op (or cursor update) as sent between peers over a joined
+/// workspace's transport, one newline-delimited JSON value per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum RemoteOp {
+    Insert(InsertOp),
+    Delete(DeleteOp),
+    Cursor { peer: String, char_idx: usize },
+}
+
+/// One colored annotation per peer cursor is tagged with this prefix so
+/// [`WorkspaceState::render_peer_cursors`] can find and replace its own
+/// previous annotations without disturbing whatever else a plugin placed on
+/// the same document via `buffer:set_annotations`.
+const PEER_CURSOR_PREFIX: &str = "\u{2503} ";
+
+const PEER_COLORS: &[&str] = &["#e06c75", "#98c379", "#61afef", "#e5c07b", "#c678dd", "#56b6c2"];
+
+/// Live state for the buffers a plugin has joined into a shared workspace.
+///
+/// One [`RgaSequence`] is kept per synced `DocumentId`. Each remote op is
+/// merged into it and then applied as its own single-character `Transaction`
+/// (see [`WorkspaceState::merge_insert`]/[`merge_delete`][Self::merge_delete])
+/// rather than diffing the whole rope, and remote cursor positions are
+/// tracked per peer and rendered as `PluginAnnotation`s (one per peer) so a
+/// plugin doesn't have to poll `remote_cursors()` to draw them.
+#[derive(Default)]
+pub(crate) struct WorkspaceState {
+    next_site_id: AtomicU64,
+    sequences: HashMap<DocumentId, RgaSequence>,
+    peer_cursors: HashMap<DocumentId, Vec<(String, usize)>>,
+    on_remote_change: HashMap<DocumentId, Vec<mlua::RegistryKey>>,
+    /// Outbound sender for the transport a document was joined with, used by
+    /// [`Self::forward_local_change`] to stream local edits to peers.
+    outbound: HashMap<DocumentId, UnboundedSender<String>>,
+    /// Documents currently having a remote-origin op applied via
+    /// [`Self::apply_char_change`]. [`Self::forward_local_change`] checks
+    /// this so the resulting document-change notification isn't mistaken
+    /// for a genuine local edit and re-forwarded to peers - without it,
+    /// every remote character would be re-inserted under a new `CharId` and
+    /// echoed straight back out, looping forever.
+    applying_remote: HashSet<DocumentId>,
+}
+
+impl WorkspaceState {
+    /// A site id for a new `RgaSequence`: the local counter hashed with a
+    /// per-process random key, so two editors joining the same workspace
+    /// don't both start handing out site 0.
+    pub(crate) fn next_site_id(&self) -> u64 {
+        use std::hash::{BuildHasher, Hasher};
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u64(self.next_site_id.fetch_add(1, Ordering::SeqCst));
+        hasher.finish()
+    }
+
+    pub(crate) fn register_remote_change(
+        &mut self,
+        document_id: DocumentId,
+        callback: mlua::RegistryKey,
+    ) {
+        self.on_remote_change
+            .entry(document_id)
+            .or_default()
+            .push(callback);
+    }
+
+    pub(crate) fn remote_cursors(&self, document_id: DocumentId) -> Vec<(String, usize)> {
+        self.peer_cursors
+            .get(&document_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_peer_cursor(&mut self, document_id: DocumentId, peer: String, char_idx: usize) {
+        let cursors = self.peer_cursors.entry(document_id).or_default();
+        if let Some(entry) = cursors.iter_mut().find(|(name, _)| *name == peer) {
+            entry.1 = char_idx;
+        } else {
+            cursors.push((peer, char_idx));
+        }
+    }
+
+    /// Start tracking `document_id` for sync with the initial text it had
+    /// when the workspace was joined (or reset to, if rejoining).
+    pub(crate) fn bootstrap_document(&mut self, document_id: DocumentId, text: &str) {
+        let site_id = self.next_site_id();
+        let mut seq = RgaSequence::new(site_id);
+        for (i, ch) in text.chars().enumerate() {
+            seq.local_insert(i, ch);
+        }
+        self.sequences.insert(document_id, seq);
+    }
+
+    /// Register the outbound half of a joined workspace's transport, so
+    /// [`Self::forward_local_change`] can stream local edits to peers.
+    pub(crate) fn register_transport(&mut self, document_id: DocumentId, sender: UnboundedSender<String>) {
+        self.outbound.insert(document_id, sender);
+    }
+
+    /// Merge a remote insert into the tracked sequence for a document and
+    /// apply just that one character to the live rope as its own
+    /// `Transaction`, so remote edits flow through the normal undo-tracked
+    /// editing path without touching any text the op didn't itself insert.
+    pub(crate) fn merge_insert(&mut self, editor: &mut Editor, document_id: DocumentId, op: InsertOp) {
+        let site_id = self.next_site_id();
+        let seq = self
+            .sequences
+            .entry(document_id)
+            .or_insert_with(|| RgaSequence::new(site_id));
+        seq.apply_insert(op);
+        if let Some(visible_idx) = seq.visible_index_of(op.id) {
+            self.applying_remote.insert(document_id);
+            Self::apply_char_change(editor, document_id, visible_idx, visible_idx, Some(op.value));
+            self.applying_remote.remove(&document_id);
+        }
+    }
+
+    /// Merge a remote delete and apply it as a one-character-wide
+    /// `Transaction` removing exactly the tombstoned character.
+    pub(crate) fn merge_delete(&mut self, editor: &mut Editor, document_id: DocumentId, op: DeleteOp) {
+        let Some(seq) = self.sequences.get_mut(&document_id) else {
+            return;
+        };
+        let visible_idx = seq.visible_index_of(op.id);
+        seq.apply_delete(op);
+        if let Some(visible_idx) = visible_idx {
+            self.applying_remote.insert(document_id);
+            Self::apply_char_change(editor, document_id, visible_idx, visible_idx + 1, None);
+            self.applying_remote.remove(&document_id);
+        }
+    }
+
+    /// Apply the local edit `start..end` -> `new_text` (as reported by
+    /// `buffer:on_change`) to the tracked sequence and stream the resulting
+    /// per-character ops to whatever transport [`Self::register_transport`]
+    /// registered for this document. A no-op if the document isn't synced.
+    pub(crate) fn forward_local_change(
+        &mut self,
+        document_id: DocumentId,
+        start: usize,
+        end: usize,
+        new_text: &str,
+    ) {
+        if self.applying_remote.contains(&document_id) {
+            return;
+        }
+
+        let Some(seq) = self.sequences.get_mut(&document_id) else {
+            return;
+        };
+
+        let mut ops = Vec::new();
+        for _ in start..end {
+            if let Some(op) = seq.local_delete(start) {
+                ops.push(RemoteOp::Delete(op));
+            }
+        }
+        for (i, ch) in new_text.chars().enumerate() {
+            ops.push(RemoteOp::Insert(seq.local_insert(start + i, ch)));
+        }
+
+        let Some(sender) = self.outbound.get(&document_id) else {
+            return;
+        };
+        for op in ops {
+            if let Ok(line) = serde_json::to_string(&op) {
+                let _ = sender.send(line);
+            }
+        }
+    }
+
+    /// Apply a single character insert/delete directly to `document_id`'s
+    /// rope as its own `Transaction`, bypassing any whole-document diff.
+    fn apply_char_change(
+        editor: &mut Editor,
+        document_id: DocumentId,
+        start: usize,
+        end: usize,
+        insert: Option<char>,
+    ) {
+        let Some(view_id) = editor
+            .tree
+            .views()
+            .find_map(|(view, _)| (view.doc == document_id).then_some(view.id))
+        else {
+            return;
+        };
+        let Some(doc) = editor.documents.get_mut(&document_id) else {
+            return;
+        };
+        let len = doc.text().len_chars();
+        let start = start.min(len);
+        let end = end.min(len).max(start);
+        let replacement = insert.map(|c| c.to_string().into());
+        let transaction =
+            helix_core::Transaction::change(doc.text(), std::iter::once((start, end, replacement)));
+        doc.apply(&transaction, view_id);
+    }
+
+    /// Replace this document's previously rendered peer-cursor annotations
+    /// (tagged with [`PEER_CURSOR_PREFIX`]) with fresh ones reflecting the
+    /// current `peer_cursors`, leaving any other annotation a plugin placed
+    /// via `buffer:set_annotations` untouched.
+    pub(crate) fn render_peer_cursors(&self, editor: &mut Editor, document_id: DocumentId) {
+        let Some(view_id) = editor
+            .tree
+            .views()
+            .find_map(|(view, _)| (view.doc == document_id).then_some(view.id))
+        else {
+            return;
+        };
+        let Some(doc) = editor.documents.get_mut(&document_id) else {
+            return;
+        };
+
+        let annots = doc.plugin_annotations.entry(view_id).or_default();
+        annots.retain(|a| !a.text.starts_with(PEER_CURSOR_PREFIX));
+
+        for (i, (peer, char_idx)) in self
+            .peer_cursors
+            .get(&document_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            annots.push(helix_view::document::PluginAnnotation {
+                char_idx,
+                text: format!("{PEER_CURSOR_PREFIX}{peer}"),
+                style: None,
+                fg: Some(PEER_COLORS[i % PEER_COLORS.len()].to_string()),
+                bg: None,
+                offset: 0,
+                is_line: false,
+                placement: helix_view::annotations::plugins::AnnotationPlacement::default(),
+                underline: None,
+                align: helix_view::annotations::plugins::AnnotationAlign::default(),
+            });
+        }
+    }
+}
+
+/// Wrapper for [`WorkspaceState`] stored in Lua app data.
+#[derive(Clone)]
+pub(crate) struct WorkspaceStateHandle(pub(crate) Arc<RwLock<WorkspaceState>>);
+
+/// `helix.workspace.join(url)` return value: a handle to the joined session.
+#[derive(Clone)]
+pub struct LuaWorkspace {
+    url: String,
+    synced_docs: Arc<RwLock<Vec<DocumentId>>>,
+}
+
+impl LuaUserData for LuaWorkspace {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("url", |_, this| Ok(this.url.clone()));
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        // ws:buffers() - the buffers currently synced in this workspace
+        methods.add_method("buffers", |_lua, this, ()| {
+            Ok(this
+                .synced_docs
+                .read()
+                .iter()
+                .map(|&id| super::buffer::LuaBuffer::new(id))
+                .collect::<Vec<_>>())
+        });
+    }
+}
+
+/// Register `helix.workspace`.
+pub fn register_workspace_api(lua: &Lua, helix_table: &LuaTable) -> Result<()> {
+    lua.set_app_data(WorkspaceStateHandle(Arc::new(RwLock::new(
+        WorkspaceState::default(),
+    ))));
+
+    let workspace_module = lua.create_table()?;
+
+    // helix.workspace.join(url) - join a shared workspace, syncing the
+    // current buffer over a TCP transport to `url` (a bare `host:port`, an
+    // optional `tcp://`/`ws://` prefix is stripped). Local edits stream out
+    // as they land (via `fire_buffer_change`, see `forward_local_change`);
+    // remote ops arrive newline-delimited JSON `RemoteOp` values and are
+    // merged from the main loop on the next tick (`LuaEngine::poll_async`).
+    let join = lua.create_function(|lua, url: String| {
+        crate::types::require_capability(lua, crate::types::Capability::Workspace)?;
+
+        let editor = crate::lua::get_editor_mut()?;
+        let (_, doc) = helix_view::current!(editor);
+        let document_id = doc.id();
+        let initial_text = doc.text().to_string();
+
+        let pending = lua
+            .app_data_ref::<crate::types::PendingAsyncOps>()
+            .ok_or_else(|| LuaError::RuntimeError("async scheduler not initialized".into()))?
+            .0
+            .clone();
+
+        let (out_tx, out_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        with_workspace_state(lua, |state| {
+            state.bootstrap_document(document_id, &initial_text);
+            state.register_transport(document_id, out_tx);
+        })?;
+
+        let addr = url
+            .trim_start_matches("ws://")
+            .trim_start_matches("tcp://")
+            .to_string();
+
+        crate::lua::scheduler::background_runtime().spawn(run_transport(addr, document_id, pending, out_rx));
+
+        Ok(LuaWorkspace {
+            url,
+            synced_docs: Arc::new(RwLock::new(vec![document_id])),
+        })
+    })?;
+    workspace_module.set("join", join)?;
+
+    helix_table.set("workspace", workspace_module)?;
+
+    Ok(())
+}
+
+/// Drive one joined workspace's transport on the background runtime: read
+/// newline-delimited JSON [`RemoteOp`]s from `addr` and queue each as a
+/// [`crate::lua::scheduler::PendingAsyncOp::WorkspaceRemoteOp`] for the next
+/// editor tick, while writing out whatever [`WorkspaceState::forward_local_change`]
+/// sends on `out_rx`. Exits quietly (logging nothing further) once the
+/// connection drops or fails to establish - a plugin notices by its
+/// `synced_docs` simply never gaining remote content.
+async fn run_transport(
+    addr: String,
+    document_id: DocumentId,
+    pending: Arc<parking_lot::Mutex<Vec<crate::lua::scheduler::PendingAsyncOp>>>,
+    mut out_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
+) {
+    let stream = match TcpStream::connect(&addr).await {
+        Ok(stream) => stream,
+        Err(_) => return,
+    };
+    let (read_half, mut write_half) = stream.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else { break };
+                if let Ok(op) = serde_json::from_str::<RemoteOp>(&line) {
+                    pending
+                        .lock()
+                        .push(crate::lua::scheduler::PendingAsyncOp::WorkspaceRemoteOp {
+                            document_id,
+                            op,
+                        });
+                }
+            }
+            outgoing = out_rx.recv() => {
+                let Some(mut outgoing) = outgoing else { break };
+                outgoing.push('\n');
+                if write_half.write_all(outgoing.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Reach into the shared workspace state; used by
+/// `LuaBuffer:on_remote_change`/`:remote_cursors` (see `super::buffer`).
+pub(crate) fn with_workspace_state<R>(
+    lua: &Lua,
+    f: impl FnOnce(&mut WorkspaceState) -> R,
+) -> LuaResult<R> {
+    let handle = lua
+        .app_data_ref::<WorkspaceStateHandle>()
+        .ok_or_else(|| LuaError::RuntimeError("workspace API not initialized".into()))?;
+    Ok(f(&mut handle.0.write()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_cursor_tracking_updates_in_place() {
+        let mut state = WorkspaceState::default();
+        let doc = DocumentId::default();
+        state.set_peer_cursor(doc, "alice".to_string(), 3);
+        state.set_peer_cursor(doc, "alice".to_string(), 7);
+        assert_eq!(state.remote_cursors(doc), vec![("alice".to_string(), 7)]);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::{PluginError, Result};
+use crate::types::{CommandMetadata, PluginMetadata};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// What we cache about one plugin, plus the filesystem fingerprint that
+/// decides whether the cache entry is still good: a plugin whose
+/// `plugin.toml` path/mtime/size haven't changed since we last parsed it
+/// doesn't need re-parsing or another Lua load just to list its commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCacheEntry {
+    pub metadata: PluginMetadata,
+    pub commands: Vec<CommandMetadata>,
+    pub path: PathBuf,
+    pub mtime: u64,
+    pub size: u64,
+}
+
+/// One entry's on-disk record: the plugin name alongside its
+/// [`PluginCacheEntry`], so the entry's filename (a hash of the name, for
+/// filesystem safety) never has to be reversed back into the name on load.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredEntry {
+    name: String,
+    entry: PluginCacheEntry,
+}
+
+/// On-disk cache of plugin metadata and command lists, keyed by plugin
+/// name. Persisted as a directory of individually brotli-compressed
+/// MessagePack files (`<cache_dir>/<hash of name>.msgpackz`), one per
+/// plugin, mirroring how nushell caches `register`ed plugin signatures
+/// instead of re-evaluating every plugin on each launch.
+///
+/// Each entry lives in its own file so adding or removing one plugin
+/// ([`Self::update`]/[`Self::remove`]) only ever touches that plugin's
+/// file, not the whole cache; [`Self::load`] likewise isolates a corrupt
+/// entry to just that file instead of discarding everything else that
+/// loaded fine.
+#[derive(Debug, Default)]
+pub struct PluginCache {
+    entries: HashMap<String, PluginCacheEntry>,
+}
+
+const CACHE_EXTENSION: &str = "msgpackz";
+
+impl PluginCache {
+    /// Load every entry found in `dir`. A missing directory yields an empty
+    /// cache; a corrupt entry file is reported and skipped, leaving every
+    /// other entry that parsed fine intact.
+    pub fn load(dir: &Path) -> Self {
+        let mut cache = Self::default();
+
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return cache;
+        };
+
+        for file in read_dir.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(CACHE_EXTENSION) {
+                continue;
+            }
+            match Self::try_load_entry(&path) {
+                Ok(stored) => {
+                    cache.entries.insert(stored.name, stored.entry);
+                }
+                Err(e) => {
+                    warn!("Plugin metadata cache entry at {:?} is corrupt, skipping: {}", path, e);
+                }
+            }
+        }
+
+        cache
+    }
+
+    fn try_load_entry(path: &Path) -> Result<StoredEntry> {
+        let compressed = std::fs::read(path)?;
+        let mut packed = Vec::new();
+        brotli::BrotliDecompress(&mut compressed.as_slice(), &mut packed)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to decompress plugin cache entry: {}", e)))?;
+        rmp_serde::from_slice(&packed)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to parse plugin cache entry: {}", e)))
+    }
+
+    /// The path an entry named `name` is stored at under `dir`. Hashed
+    /// rather than using `name` verbatim so plugin names containing
+    /// characters that aren't safe in a filename still round-trip.
+    fn entry_path(dir: &Path, name: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        dir.join(format!("{:016x}.{CACHE_EXTENSION}", hasher.finish()))
+    }
+
+    fn write_entry(dir: &Path, name: &str, entry: &PluginCacheEntry) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+
+        let stored = StoredEntry {
+            name: name.to_string(),
+            entry: entry.clone(),
+        };
+        let packed = rmp_serde::to_vec(&stored)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to serialize plugin cache entry: {}", e)))?;
+
+        let mut compressed = Vec::new();
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 9, 22);
+        writer
+            .write_all(&packed)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to compress plugin cache entry: {}", e)))?;
+        drop(writer);
+
+        std::fs::write(Self::entry_path(dir, name), compressed)?;
+        Ok(())
+    }
+
+    /// Fingerprint `plugin_dir`'s `plugin.toml` (falling back to the
+    /// directory itself for plugins with no metadata file) to compare
+    /// against a cached entry's `mtime`/`size`.
+    pub fn fingerprint(plugin_dir: &Path) -> Option<(u64, u64)> {
+        let metadata_file = plugin_dir.join("plugin.toml");
+        let stat_target = if metadata_file.exists() { &metadata_file } else { plugin_dir };
+        let metadata = std::fs::metadata(stat_target).ok()?;
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        Some((mtime, metadata.len()))
+    }
+
+    /// Look up a cache entry for `name`, but only if `path`'s current
+    /// fingerprint still matches what was cached.
+    pub fn fresh_entry(&self, name: &str, path: &Path) -> Option<&PluginCacheEntry> {
+        let entry = self.entries.get(name)?;
+        let (mtime, size) = Self::fingerprint(path)?;
+        (entry.path == path && entry.mtime == mtime && entry.size == size).then_some(entry)
+    }
+
+    /// Insert (or replace) `name`'s entry and immediately persist just that
+    /// one entry's file under `dir`, without touching any other plugin's.
+    pub fn update(&mut self, dir: &Path, name: String, entry: PluginCacheEntry) -> Result<()> {
+        Self::write_entry(dir, &name, &entry)?;
+        self.entries.insert(name, entry);
+        Ok(())
+    }
+
+    /// Remove `name`'s entry and delete its file under `dir`, if present.
+    pub fn remove(&mut self, dir: &Path, name: &str) -> Result<()> {
+        self.entries.remove(name);
+        match std::fs::remove_file(Self::entry_path(dir, name)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{PluginError, Result};
+use crate::lua::cache::{PluginCache, PluginCacheEntry};
 use crate::types::{Plugin, PluginMetadata};
 use log::{debug, info, warn};
 use std::path::{Path, PathBuf};
@@ -54,8 +55,75 @@ impl PluginLoader {
         Ok(plugins)
     }
 
-    /// Load plugin metadata from a directory
-    fn load_plugin_metadata(&self, path: &Path) -> Result<Plugin> {
+    /// Like [`Self::discover_plugins`], but consult `cache` first: a plugin
+    /// directory whose `plugin.toml` (path/mtime/size) matches a cached
+    /// entry reuses that metadata instead of re-reading and re-parsing the
+    /// file. Callers should write `cache` back to disk after loading, once
+    /// each plugin's registered commands are known.
+    pub fn discover_plugins_cached(&self, cache: &PluginCache) -> Result<Vec<Plugin>> {
+        let mut plugins = Vec::new();
+
+        for dir in &self.plugin_dirs {
+            if !dir.exists() || !dir.is_dir() {
+                continue;
+            }
+
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if !path.is_dir() {
+                    continue;
+                }
+
+                let cached_metadata = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|name| cache.fresh_entry(name, &path))
+                    .map(|entry| entry.metadata.clone());
+
+                let metadata = match cached_metadata {
+                    Some(metadata) => {
+                        debug!("Using cached metadata for plugin at {:?}", path);
+                        metadata
+                    }
+                    None => match self.load_plugin_metadata(&path) {
+                        Ok(plugin) => plugin.metadata,
+                        Err(e) => {
+                            warn!("Failed to load plugin at {:?}: {}", path, e);
+                            continue;
+                        }
+                    },
+                };
+
+                info!("Discovered plugin: {} at {:?}", metadata.name, path);
+                plugins.push(Plugin {
+                    metadata,
+                    path,
+                    enabled: true,
+                });
+            }
+        }
+
+        Ok(plugins)
+    }
+
+    /// Build this plugin's cache entry from its current metadata, commands,
+    /// and on-disk fingerprint.
+    pub fn cache_entry(&self, plugin: &Plugin, commands: Vec<crate::types::CommandMetadata>) -> Option<PluginCacheEntry> {
+        let (mtime, size) = PluginCache::fingerprint(&plugin.path)?;
+        Some(PluginCacheEntry {
+            metadata: plugin.metadata.clone(),
+            commands,
+            path: plugin.path.clone(),
+            mtime,
+            size,
+        })
+    }
+
+    /// Load plugin metadata from a directory. `pub(crate)` so a single
+    /// plugin can be (re)loaded by path at runtime (see
+    /// `PluginManager::load_plugin_from_path`) without discovering every
+    /// plugin in the configured directories.
+    pub(crate) fn load_plugin_metadata(&self, path: &Path) -> Result<Plugin> {
         // Check for plugin.toml
         let metadata_file = path.join("plugin.toml");
         let metadata = if metadata_file.exists() {
@@ -2,17 +2,62 @@ use crate::error::{PluginError, Result};
 use crate::types::{EventType, PluginEvent};
 use helix_view::Editor;
 use mlua::prelude::*;
-use mlua::RegistryKey;
+use mlua::{HookTriggers, RegistryKey, VmState};
 use parking_lot::RwLock;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::time::{Duration, Instant};
+
+/// Substring common to every error the debug hook in [`LuaEngine::install_budget_hook`]
+/// raises, so a callback's `map_err` can tell a budget timeout apart from an
+/// ordinary Lua error without a dedicated `mlua::Error` variant to match on.
+const BUDGET_EXCEEDED: &str = "plugin exceeded its execution budget";
+
+/// Tracks how much of a callback's resource budget has been spent, checked
+/// by the debug hook installed on every [`LuaEngine`]'s `Lua` instance.
+/// Lives in Lua's own app data (like the rest of this module's cross-cutting
+/// state) so the hook closure - which only gets `&Lua` - can reach it
+/// without capturing anything from the engine.
+struct CallBudget {
+    max_duration: Duration,
+    max_instructions: u64,
+    deadline: Instant,
+    instructions_used: u64,
+}
+
+impl CallBudget {
+    fn new(limits: &crate::types::PluginLimits) -> Self {
+        Self {
+            max_duration: Duration::from_millis(limits.max_duration_ms),
+            max_instructions: limits.max_instructions,
+            deadline: Instant::now(),
+            instructions_used: 0,
+        }
+    }
+}
 
 thread_local! {
     static CURRENT_EDITOR: RefCell<Option<*mut Editor>> = const { RefCell::new(None) };
 }
 
+/// A `Waker` that does nothing when woken. `LuaEngine::poll_async` re-polls
+/// every in-flight [`AsyncTask`] unconditionally on every editor tick rather
+/// than waiting to be woken, so there's nothing useful for a real waker to
+/// do - this just satisfies `Future::poll`'s signature.
+fn noop_waker() -> Waker {
+    fn no_op(_: *const ()) {}
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, no_op, no_op, no_op);
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
+
 /// Helper to set the current editor context during a function execution
 pub(crate) fn with_editor_context<F, R>(editor: &mut Editor, f: F) -> R
 where
@@ -42,9 +87,42 @@ pub(crate) fn get_editor_mut() -> std::result::Result<&'static mut Editor, mlua:
 }
 
 pub mod api;
+pub mod cache;
 pub mod loader;
+pub(crate) mod scheduler;
 
 type EventHandlers = HashMap<EventType, Vec<(String, RegistryKey)>>;
+/// User-defined events raised by `helix.emit`: event name -> Vec<(plugin_name, callback_ref)>,
+/// mirroring `EventHandlers` but keyed by an arbitrary string instead of the
+/// fixed `EventType` set, so plugins can define their own pub/sub channels.
+type CustomEventHandlers = HashMap<String, Vec<(String, RegistryKey)>>;
+
+/// A `helix.register_command_async` call in progress, driven one step per
+/// editor tick by [`LuaEngine::poll_async`] instead of running to
+/// completion in a single synchronous call. Built from
+/// `LuaFunction::call_async`, so the plugin's handler can suspend mid-call
+/// at any `helix.timer`-style await point.
+struct AsyncTask {
+    plugin_name: String,
+    command_name: String,
+    future: Pin<Box<dyn Future<Output = mlua::Result<()>> + Send>>,
+}
+
+/// Free every `render` callback a [`crate::types::PanelNode`] tree holds in
+/// the Lua registry, recursing into `Split` children. Consumes `node` since
+/// a [`RegistryKey`] is only meaningful to free once.
+fn free_panel_node(lua: &Lua, node: crate::types::PanelNode) {
+    match node {
+        crate::types::PanelNode::Split { children, .. } => {
+            for (_, child) in children {
+                free_panel_node(lua, child);
+            }
+        }
+        crate::types::PanelNode::Widget { render, .. } => {
+            lua.remove_registry_value(render).ok();
+        }
+    }
+}
 
 /// Lua scripting engine for Helix plugins
 pub struct LuaEngine {
@@ -52,11 +130,30 @@ pub struct LuaEngine {
     lua: Lua,
     /// Registered event handlers: EventType -> Vec<(plugin_name, callback_ref)>
     event_handlers: Arc<RwLock<EventHandlers>>,
+    /// Handlers subscribed via `helix.on` to a plugin-defined event name
+    /// rather than a built-in `EventType`, raised with `helix.emit`.
+    custom_handlers: Arc<RwLock<CustomEventHandlers>>,
     /// Loaded plugins by name
     /// Loaded plugins by name
     plugins: HashMap<String, crate::types::Plugin>,
     /// Registered commands: name -> (metadata, callback_ref)
     commands: Arc<RwLock<HashMap<String, (crate::types::CommandMetadata, RegistryKey)>>>,
+    /// Command names each plugin registered, so `unload_plugin` can remove
+    /// exactly its entries from `commands` without disturbing others.
+    registered_commands: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    /// Names of commands registered via `helix.register_command_async`;
+    /// `execute_command` consults this to decide whether to run the
+    /// handler synchronously or drive it as an [`AsyncTask`].
+    async_commands: Arc<RwLock<HashSet<String>>>,
+    /// In-flight `register_command_async` calls, keyed by nothing in
+    /// particular - each tick, `poll_async` polls every entry once and
+    /// keeps whichever are still pending.
+    async_tasks: Arc<parking_lot::Mutex<Vec<AsyncTask>>>,
+    /// Modules loaded via a plugin's scoped `require`, keyed by
+    /// `(plugin_name, module_name)` so two plugins can both `require("utils")`
+    /// without colliding, and a module is only read and executed once per
+    /// plugin no matter how many files `require` it.
+    module_cache: Arc<RwLock<HashMap<(String, String), RegistryKey>>>,
     /// Builtin editor command registry
     builtin_commands: Option<Arc<dyn crate::types::EditorCommandRegistry>>,
     /// UI callbacks: (plugin_name, callback_id) -> callback_ref
@@ -65,6 +162,17 @@ pub struct LuaEngine {
     next_ui_callback_id: Arc<std::sync::atomic::AtomicU64>,
     /// UI handler registry
     ui_handler: Option<Arc<dyn crate::types::UiHandler>>,
+    /// Results produced off-thread by `helix.async`/async buffer methods that
+    /// are still waiting to be applied to the editor
+    pending_async: Arc<parking_lot::Mutex<Vec<scheduler::PendingAsyncOp>>>,
+    /// Per-buffer `on_change`/`on_cursor_move`/`on_diagnostics` callbacks
+    buffer_callbacks: Arc<RwLock<api::buffer::BufferCallbacks>>,
+    /// Capability grants per plugin name, consulted by gated API functions
+    capabilities: Arc<parking_lot::RwLock<HashMap<String, HashSet<crate::types::Capability>>>>,
+    /// Docked panels created via `helix.ui.create_panel`, keyed by the
+    /// plugin name that owns them. `redraw_panels` re-invokes each cached
+    /// panel's `render` callbacks on demand.
+    panels: Arc<RwLock<HashMap<String, crate::types::CachedPanel>>>,
 }
 
 impl LuaEngine {
@@ -88,7 +196,10 @@ impl LuaEngine {
             PluginError::InitializationFailed(format!("Failed to setup sandbox: {}", e))
         })?;
 
+        Self::install_budget_hook(&lua, &crate::types::PluginLimits::default());
+
         let event_handlers = Arc::new(RwLock::new(HashMap::new()));
+        let custom_handlers = Arc::new(RwLock::new(HashMap::new()));
         let commands = Arc::new(RwLock::new(HashMap::new()));
         let ui_callbacks = Arc::new(RwLock::new(HashMap::new()));
         let next_ui_callback_id = Arc::new(std::sync::atomic::AtomicU64::new(1));
@@ -96,12 +207,21 @@ impl LuaEngine {
         Ok(Self {
             lua,
             event_handlers,
+            custom_handlers,
             plugins: HashMap::new(),
             commands,
+            registered_commands: Arc::new(RwLock::new(HashMap::new())),
+            async_commands: Arc::new(RwLock::new(HashSet::new())),
+            async_tasks: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            module_cache: Arc::new(RwLock::new(HashMap::new())),
             builtin_commands: None,
             ui_callbacks,
             next_ui_callback_id,
             ui_handler: None,
+            pending_async: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            buffer_callbacks: Arc::new(RwLock::new(api::buffer::BufferCallbacks::default())),
+            capabilities: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            panels: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -125,15 +245,115 @@ impl LuaEngine {
             PluginError::InitializationFailed(format!("Failed to setup sandbox: {}", e))
         })?;
 
+        Self::install_budget_hook(&lua, &crate::types::PluginLimits::default());
+
         self.lua = lua;
         self.event_handlers.write().clear();
+        self.custom_handlers.write().clear();
         self.commands.write().clear();
+        self.registered_commands.write().clear();
+        self.async_commands.write().clear();
+        self.async_tasks.lock().clear();
+        self.module_cache.write().clear();
         self.plugins.clear();
         self.ui_callbacks.write().clear();
+        // The old `self.lua` above is being replaced wholesale, so the
+        // registry keys any cached panel holds are already meaningless -
+        // just drop the cache rather than trying to free them one by one.
+        self.panels.write().clear();
 
         Ok(())
     }
 
+    /// Install the debug hook that enforces `limits` on every Lua call,
+    /// plus the [`CallBudget`] app data it reads/updates. Checked every
+    /// 1000 VM instructions rather than every one, so well-behaved plugins
+    /// pay negligible overhead.
+    fn install_budget_hook(lua: &Lua, limits: &crate::types::PluginLimits) {
+        lua.set_app_data(CallBudget::new(limits));
+
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(1000),
+            |lua, _debug| {
+                let mut budget = lua.app_data_mut::<CallBudget>().ok_or_else(|| {
+                    mlua::Error::RuntimeError("plugin call budget missing".to_string())
+                })?;
+
+                budget.instructions_used = budget.instructions_used.saturating_add(1000);
+                if budget.instructions_used > budget.max_instructions {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "{} (instruction count)",
+                        BUDGET_EXCEEDED
+                    )));
+                }
+                if Instant::now() >= budget.deadline {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "{} (wall-clock time)",
+                        BUDGET_EXCEEDED
+                    )));
+                }
+
+                Ok(VmState::Continue)
+            },
+        );
+    }
+
+    /// Set `_current_plugin_name` to `plugin_name` for the duration of `f`,
+    /// restoring it to `Nil` afterward. `load_plugin` sets this global while
+    /// running a plugin's own top-level chunk, but every other entry point
+    /// that later invokes one of that plugin's callbacks - commands, event
+    /// handlers, UI callbacks, async resumes - must re-establish it itself,
+    /// or capability checks and `helix.storage` calls made from inside the
+    /// callback misattribute to "unknown" and get denied/miscollapsed.
+    fn with_plugin_context<F, R>(&self, plugin_name: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let globals = self.lua.globals();
+        let _ = globals.set("_current_plugin_name", plugin_name);
+        let result = f();
+        let _ = globals.set("_current_plugin_name", LuaValue::Nil);
+        result
+    }
+
+    /// Reset the instruction counter and wall-clock deadline for a fresh
+    /// callback invocation. Must run right before every `callback.call` -
+    /// otherwise elapsed editor uptime would accumulate in `deadline` and
+    /// eventually trip on a plugin that was never actually slow.
+    fn reset_budget(&self) {
+        if let Some(mut budget) = self.lua.app_data_mut::<CallBudget>() {
+            budget.deadline = Instant::now() + budget.max_duration;
+            budget.instructions_used = 0;
+        }
+    }
+
+    /// Map a Lua error raised while running a plugin callback, recognizing
+    /// a budget timeout raised by [`Self::install_budget_hook`] as
+    /// [`PluginError::Timeout`] rather than the caller's usual wrapping.
+    fn map_callback_error(e: mlua::Error, wrap: impl FnOnce(String) -> PluginError) -> PluginError {
+        if e.to_string().contains(BUDGET_EXCEEDED) {
+            PluginError::Timeout(e.to_string())
+        } else {
+            wrap(e.to_string())
+        }
+    }
+
+    /// Convert a `serde_json::Value` to a `LuaValue`, recursively - nested
+    /// objects and arrays included, unlike the old `get_config` converter
+    /// that only handled top-level strings/numbers/bools. Thin wrapper over
+    /// `Lua::to_value` (mlua's serde support) so every call site in this
+    /// module shares one conversion path instead of hand-rolling another
+    /// partial one.
+    fn json_to_lua(lua: &Lua, value: &serde_json::Value) -> mlua::Result<LuaValue> {
+        lua.to_value(value)
+    }
+
+    /// The inverse of [`Self::json_to_lua`]: recursively convert a `LuaValue`
+    /// back to a `serde_json::Value` via `Lua::from_value`.
+    fn lua_to_json(lua: &Lua, value: LuaValue) -> mlua::Result<serde_json::Value> {
+        lua.from_value(value)
+    }
+
     /// Set the builtin command registry
     pub fn set_builtin_command_registry(
         &mut self,
@@ -169,6 +389,26 @@ impl LuaEngine {
             .set_app_data(crate::types::UiCallbackCounter(Arc::clone(
                 &self.next_ui_callback_id,
             )));
+        self.lua
+            .set_app_data(crate::types::PendingAsyncOps(Arc::clone(
+                &self.pending_async,
+            )));
+        self.lua
+            .set_app_data(api::buffer::BufferCallbackRegistry(Arc::clone(
+                &self.buffer_callbacks,
+            )));
+        self.lua
+            .set_app_data(crate::types::CapabilityRegistry(Arc::clone(
+                &self.capabilities,
+            )));
+        self.lua
+            .set_app_data(crate::types::PanelRegistry(Arc::clone(&self.panels)));
+
+        self.lua.set_app_data(CallBudget::new(&config.limits));
+        self.lua
+            .set_memory_limit(config.limits.max_memory_bytes)
+            .map_err(PluginError::LuaError)?;
+
         self.lua.set_app_data(config);
 
         // Create the main helix table
@@ -186,6 +426,10 @@ impl LuaEngine {
         api::register_window_api(&self.lua, &helix)?;
         api::register_lsp_api(&self.lua, &helix)?;
         api::register_log_api(&self.lua, &helix)?;
+        api::register_async_api(&self.lua, &helix)?;
+        api::register_workspace_api(&self.lua, &helix)?;
+        api::register_json_api(&self.lua, &helix)?;
+        api::register_storage_api(&self.lua, &helix)?;
 
         // Register version info
         helix.set("version", env!("CARGO_PKG_VERSION"))?;
@@ -199,14 +443,13 @@ impl LuaEngine {
     /// Register the event API
     fn register_event_api(&self, helix: &LuaTable) -> Result<()> {
         let event_handlers: Arc<RwLock<EventHandlers>> = Arc::clone(&self.event_handlers);
+        let custom_handlers: Arc<RwLock<CustomEventHandlers>> = Arc::clone(&self.custom_handlers);
 
-        // helix.on(event_name, callback) - Subscribe to an event
+        // helix.on(event_name, callback) - Subscribe to a built-in EventType
+        // by name, or to an arbitrary plugin-defined event raised elsewhere
+        // with `helix.emit`.
         let on = self.lua.create_function(
             move |lua, (event_name, callback): (String, LuaFunction)| {
-                let event_type = EventType::from_str(&event_name).map_err(|_| {
-                    LuaError::RuntimeError(format!("Invalid event type: {}", event_name))
-                })?;
-
                 let plugin_name = lua
                     .globals()
                     .get::<String>("_current_plugin_name")
@@ -214,12 +457,22 @@ impl LuaEngine {
 
                 let callback_ref = lua.create_registry_value(callback)?;
 
-                // Add to event handlers
-                let mut handlers = event_handlers.write();
-                handlers
-                    .entry(event_type)
-                    .or_default()
-                    .push((plugin_name, callback_ref));
+                match EventType::from_str(&event_name) {
+                    Ok(event_type) => {
+                        event_handlers
+                            .write()
+                            .entry(event_type)
+                            .or_default()
+                            .push((plugin_name, callback_ref));
+                    }
+                    Err(()) => {
+                        custom_handlers
+                            .write()
+                            .entry(event_name)
+                            .or_default()
+                            .push((plugin_name, callback_ref));
+                    }
+                }
 
                 Ok(())
             },
@@ -227,6 +480,57 @@ impl LuaEngine {
 
         helix.set("on", on)?;
 
+        // helix.emit(event_name, payload) - Raise a plugin-defined event,
+        // following quectocraft's broadcast/plugin-message model: any
+        // plugin can notify cooperating plugins without the editor knowing
+        // about the event at all.
+        let custom_handlers_for_emit = Arc::clone(&self.custom_handlers);
+        let emit = self.lua.create_function(
+            move |lua, (event_name, payload): (String, LuaTable)| {
+                let plugin_name = lua
+                    .globals()
+                    .get::<String>("_current_plugin_name")
+                    .unwrap_or_else(|_| "unknown".to_string());
+                payload.set("_source", plugin_name)?;
+
+                // Resolve callbacks to live LuaFunctions under the read
+                // lock, then drop it before calling back into Lua - a
+                // subscriber emitting another event from its handler must
+                // not deadlock on a lock this function still held.
+                let callbacks: Vec<(String, LuaFunction)> = {
+                    let handlers = custom_handlers_for_emit.read();
+                    handlers
+                        .get(&event_name)
+                        .map(|subscribers| {
+                            subscribers
+                                .iter()
+                                .filter_map(|(owner, callback_ref)| {
+                                    lua.registry_value::<LuaFunction>(callback_ref)
+                                        .ok()
+                                        .map(|f| (owner.clone(), f))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+
+                for (owner, callback) in callbacks {
+                    if let Err(e) = callback.call::<()>(payload.clone()) {
+                        log::warn!(
+                            "Custom event handler in plugin '{}' for '{}' failed: {}",
+                            owner,
+                            event_name,
+                            e
+                        );
+                    }
+                }
+
+                Ok(())
+            },
+        )?;
+
+        helix.set("emit", emit)?;
+
         Ok(())
     }
 
@@ -240,32 +544,17 @@ impl LuaEngine {
                 .unwrap_or_else(|_| "unknown".to_string());
 
             if let Some(config) = lua.app_data_ref::<crate::types::PluginConfig>() {
-                if let Some(plugin_config) = config.plugins.iter().find(|p| p.name == plugin_name) {
-                    // Convert serde_json::Value to LuaValue
-                    let val = match &plugin_config.config {
-                        serde_json::Value::Object(map) => {
-                            let table = lua.create_table()?;
-                            for (k, v) in map {
-                                // Simple conversion for common types
-                                match v {
-                                    serde_json::Value::String(s) => {
-                                        table.set(k.clone(), s.clone())?
-                                    }
-                                    serde_json::Value::Number(n) => {
-                                        table.set(k.clone(), n.as_f64().unwrap_or(0.0))?
-                                    }
-                                    serde_json::Value::Bool(b) => table.set(k.clone(), *b)?,
-                                    _ => {} // Skip complex types for now
-                                }
-                            }
-                            Some(table)
-                        }
-                        _ => None,
-                    };
-                    return Ok(val);
+                if let Some(plugin_config) = config
+                    .plugins
+                    .iter()
+                    .find(|p| p.effective_name() == plugin_name)
+                {
+                    // The whole config blob, nested objects/arrays included -
+                    // not just its top-level string/number/bool keys.
+                    return Self::json_to_lua(lua, &plugin_config.config);
                 }
             }
-            Ok(None)
+            Ok(LuaValue::Nil)
         })?;
 
         helix.set("get_config", get_config)?;
@@ -276,6 +565,7 @@ impl LuaEngine {
     /// Register the command API
     fn register_command_api(&self, helix: &LuaTable) -> Result<()> {
         let commands = Arc::clone(&self.commands);
+        let registered_commands = Arc::clone(&self.registered_commands);
 
         // helix.register_command({ ... })
         let reg_fn = self.lua.create_function(move |lua, table: LuaTable| {
@@ -288,6 +578,11 @@ impl LuaEngine {
                 .get("handler")
                 .map_err(|_| LuaError::RuntimeError("Command handler function required".into()))?;
 
+            let plugin_name = lua
+                .globals()
+                .get::<String>("_current_plugin_name")
+                .unwrap_or_else(|_| "unknown".to_string());
+
             let callback_ref = lua.create_registry_value(handler)?;
 
             let meta = crate::types::CommandMetadata {
@@ -296,12 +591,61 @@ impl LuaEngine {
                 args,
             };
 
-            commands.write().insert(name, (meta, callback_ref));
+            commands.write().insert(name.clone(), (meta, callback_ref));
+            registered_commands
+                .write()
+                .entry(plugin_name)
+                .or_default()
+                .insert(name);
             Ok(())
         })?;
 
         helix.set("register_command", reg_fn)?;
 
+        let commands_async = Arc::clone(&self.commands);
+        let registered_commands_async = Arc::clone(&self.registered_commands);
+        let async_commands = Arc::clone(&self.async_commands);
+
+        // helix.register_command_async({ ... }) - same shape as
+        // `register_command`, except `handler` is run with `call_async`
+        // instead of `call`, so it may suspend at a `helix.timer`-style
+        // await point instead of running to completion in one synchronous
+        // call. See `execute_command` and `poll_async`.
+        let reg_async_fn = self.lua.create_function(move |lua, table: LuaTable| {
+            let name: String = table
+                .get("name")
+                .map_err(|_| LuaError::RuntimeError("Command name required".into()))?;
+            let doc: String = table.get("doc").unwrap_or_else(|_| "".into());
+            let args: Option<String> = table.get("args").ok();
+            let handler: LuaFunction = table
+                .get("handler")
+                .map_err(|_| LuaError::RuntimeError("Command handler function required".into()))?;
+
+            let plugin_name = lua
+                .globals()
+                .get::<String>("_current_plugin_name")
+                .unwrap_or_else(|_| "unknown".to_string());
+
+            let callback_ref = lua.create_registry_value(handler)?;
+
+            let meta = crate::types::CommandMetadata {
+                name: name.clone(),
+                doc,
+                args,
+            };
+
+            commands_async.write().insert(name.clone(), (meta, callback_ref));
+            async_commands.write().insert(name.clone());
+            registered_commands_async
+                .write()
+                .entry(plugin_name)
+                .or_default()
+                .insert(name);
+            Ok(())
+        })?;
+
+        helix.set("register_command_async", reg_async_fn)?;
+
         Ok(())
     }
 
@@ -313,25 +657,94 @@ impl LuaEngine {
         args: Vec<String>,
     ) -> Result<()> {
         let commands = self.commands.read();
-        if let Some((_, callback_ref)) = commands.get(name) {
-            let callback: LuaFunction = self.lua.registry_value(callback_ref).map_err(|e| {
-                PluginError::CommandExecutionFailed(format!("Failed to retrieve callback: {}", e))
-            })?;
-
-            with_editor_context(editor, || {
-                callback.call::<()>(args).map_err(|e| {
-                    PluginError::CommandExecutionFailed(format!("Execution failed: {}", e))
-                })
-            })?;
-        } else {
+        let Some((_, callback_ref)) = commands.get(name) else {
             return Err(PluginError::CommandExecutionFailed(format!(
                 "Command not found: {}",
                 name
             )));
+        };
+        let callback: LuaFunction = self.lua.registry_value(callback_ref).map_err(|e| {
+            PluginError::CommandExecutionFailed(format!("Failed to retrieve callback: {}", e))
+        })?;
+        let is_async = self.async_commands.read().contains(name);
+        drop(commands);
+
+        self.reset_budget();
+        let plugin_name = self.owning_plugin(name);
+
+        if is_async {
+            let mut future: Pin<Box<dyn Future<Output = mlua::Result<()>> + Send>> =
+                Box::pin(async move { callback.call_async::<()>(args).await });
+
+            let poll = self
+                .with_plugin_context(&plugin_name, || self.drive_async_task(editor, &mut future));
+            if poll.is_pending() {
+                self.async_tasks.lock().push(AsyncTask {
+                    plugin_name,
+                    command_name: name.to_string(),
+                    future,
+                });
+            }
+        } else {
+            let result = self.with_plugin_context(&plugin_name, || {
+                with_editor_context(editor, || {
+                    callback.call::<LuaValue>(args).map_err(|e| {
+                        Self::map_callback_error(e, |msg| {
+                            PluginError::CommandExecutionFailed(format!("Execution failed: {}", msg))
+                        })
+                    })
+                })
+            })?;
+
+            // A handler may return a structured value (table, number, ...)
+            // rather than nothing; `execute_command` has no caller to hand
+            // it back to yet, but logging it keeps it visible for `:plugin`
+            // development instead of silently discarding it as before.
+            if !matches!(result, LuaValue::Nil) {
+                match Self::lua_to_json(&self.lua, result) {
+                    Ok(value) => log::debug!("Command '{}' returned: {}", name, value),
+                    Err(e) => log::warn!("Command '{}' returned a non-JSON-representable value: {}", name, e),
+                }
+            }
         }
+
         Ok(())
     }
 
+    /// The plugin that registered `command_name`, for attributing an
+    /// [`AsyncTask`] so `unload_plugin` can cancel it along with the rest of
+    /// that plugin's state. Falls back to `"unknown"` if the bookkeeping in
+    /// `registered_commands` is somehow missing an entry.
+    fn owning_plugin(&self, command_name: &str) -> String {
+        self.registered_commands
+            .read()
+            .iter()
+            .find(|(_, names)| names.contains(command_name))
+            .map(|(plugin, _)| plugin.clone())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Poll `future` once, re-establishing the `CURRENT_EDITOR` thread-local
+    /// around the poll so editor APIs called from within it (on either side
+    /// of an await point) see `editor`. The pointer is only valid for the
+    /// duration of this call - it's cleared again before returning,
+    /// regardless of whether the future is done.
+    fn drive_async_task(
+        &self,
+        editor: &mut Editor,
+        future: &mut Pin<Box<dyn Future<Output = mlua::Result<()>> + Send>>,
+    ) -> Poll<mlua::Result<()>> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        CURRENT_EDITOR.with(|e| *e.borrow_mut() = Some(editor as *mut _));
+        let _runtime_guard = scheduler::background_runtime().enter();
+        let poll = future.as_mut().poll(&mut cx);
+        CURRENT_EDITOR.with(|e| *e.borrow_mut() = None);
+
+        poll
+    }
+
     /// Get all registered commands metadata
     pub fn get_commands(&self) -> Vec<crate::types::CommandMetadata> {
         self.commands
@@ -340,6 +753,20 @@ impl LuaEngine {
             .map(|(meta, _)| meta.clone())
             .collect()
     }
+
+    /// Get the commands `plugin_name` registered, for writing back to the
+    /// plugin metadata cache after it loads.
+    pub fn get_commands_for_plugin(&self, plugin_name: &str) -> Vec<crate::types::CommandMetadata> {
+        let Some(names) = self.registered_commands.read().get(plugin_name).cloned() else {
+            return Vec::new();
+        };
+        let commands = self.commands.read();
+        names
+            .iter()
+            .filter_map(|name| commands.get(name).map(|(meta, _)| meta.clone()))
+            .collect()
+    }
+
     /// Handle a UI/Picker callback from the editor
     pub fn handle_ui_callback(
         &self,
@@ -349,6 +776,7 @@ impl LuaEngine {
         value: serde_json::Value,
     ) -> Result<()> {
         let mut callbacks = self.ui_callbacks.write();
+        let owning_plugin = plugin_name.clone();
         if let Some(callback_ref) = callbacks.remove(&(plugin_name, callback_id)) {
             let callback: LuaFunction = self
                 .lua
@@ -357,10 +785,13 @@ impl LuaEngine {
 
             let lua_value = self.lua.to_value(&value).map_err(PluginError::LuaError)?;
 
-            with_editor_context(editor, || {
-                callback
-                    .call::<()>(lua_value)
-                    .map_err(PluginError::LuaError)
+            self.reset_budget();
+            self.with_plugin_context(&owning_plugin, || {
+                with_editor_context(editor, || {
+                    callback.call::<()>(lua_value).map_err(|e| {
+                        Self::map_callback_error(e, |msg| PluginError::LuaError(mlua::Error::RuntimeError(msg)))
+                    })
+                })
             })?;
         }
         Ok(())
@@ -382,11 +813,48 @@ impl LuaEngine {
         let globals = self.lua.globals();
         globals.set("_current_plugin_name", plugin.metadata.name.clone())?;
 
+        // Record this plugin's capability grants before running its code, so
+        // gated API calls made during load (or later, from its callbacks)
+        // are checked against what `plugin.toml` actually declared.
+        self.capabilities.write().insert(
+            plugin.metadata.name.clone(),
+            plugin.metadata.capabilities.iter().copied().collect(),
+        );
+
+        // Give this plugin its own environment table rather than running its
+        // chunk against the shared globals: a bare global the plugin
+        // defines (or overwrites) lands on `env`, not `_G`, so it can't
+        // clobber another plugin's state of the same name. Reads of
+        // anything *not* set on `env` - `helix`, `string`, `pairs`, and so
+        // on - fall through to the real globals via the metatable, so
+        // nothing but isolation changes from the plugin's point of view.
+        let env = self.lua.create_table().map_err(PluginError::LuaError)?;
+        let env_meta = self.lua.create_table().map_err(PluginError::LuaError)?;
+        env_meta
+            .set("__index", self.lua.globals())
+            .map_err(PluginError::LuaError)?;
+        env.set_metatable(Some(env_meta));
+
+        // `require("foo.bar")` resolves to `<plugin dir>/foo/bar.lua`,
+        // executed with the same `env` so sub-modules see `helix` and can
+        // themselves `require` further modules (env's own "require" key
+        // shadows the real one for the whole plugin, submodules included,
+        // since their chunk environments fall back to `env`).
+        let require = Self::make_require(
+            &self.lua,
+            plugin.metadata.name.clone(),
+            plugin.path.clone(),
+            env.clone(),
+            Arc::clone(&self.module_cache),
+        )?;
+        env.set("require", require).map_err(PluginError::LuaError)?;
+
         // Load and execute the plugin
         let code = std::fs::read_to_string(&entry_file)?;
         self.lua
             .load(&code)
             .set_name(&plugin.metadata.name)
+            .set_environment(env)
             .exec()
             .map_err(PluginError::LuaError)?;
 
@@ -398,6 +866,111 @@ impl LuaEngine {
         Ok(())
     }
 
+    /// Build the `require` function installed on a plugin's chunk
+    /// environment. Modules are resolved relative to `plugin_path` (dots in
+    /// the module name become path separators, `.lua` is appended), executed
+    /// once under `env`, and cached in `module_cache` by
+    /// `(plugin_name, module_name)` so a later `require` of the same module -
+    /// from this plugin's entry file or any of its other modules - returns
+    /// the same value instead of re-running the file.
+    fn make_require(
+        lua: &Lua,
+        plugin_name: String,
+        plugin_path: std::path::PathBuf,
+        env: LuaTable,
+        module_cache: Arc<RwLock<HashMap<(String, String), RegistryKey>>>,
+    ) -> mlua::Result<LuaFunction> {
+        lua.create_function(move |lua, module_name: String| {
+            let cache_key = (plugin_name.clone(), module_name.clone());
+            if let Some(cached) = module_cache.read().get(&cache_key) {
+                return lua.registry_value::<LuaValue>(cached);
+            }
+
+            let relative = module_name.replace('.', std::path::MAIN_SEPARATOR_STR);
+            let module_path = plugin_path.join(format!("{}.lua", relative));
+            let code = std::fs::read_to_string(&module_path).map_err(|e| {
+                mlua::Error::RuntimeError(format!(
+                    "module '{}' not found (looked for {}): {}",
+                    module_name,
+                    module_path.display(),
+                    e
+                ))
+            })?;
+
+            let module_value: LuaValue = lua
+                .load(&code)
+                .set_name(&format!("{}:{}", plugin_name, module_name))
+                .set_environment(env.clone())
+                .eval()?;
+
+            let cache_ref = lua.create_registry_value(module_value.clone())?;
+            module_cache.write().insert(cache_key, cache_ref);
+
+            Ok(module_value)
+        })
+    }
+
+    /// Build the `event` table passed to a Lua event handler.
+    fn event_data_table(&self, event: &PluginEvent) -> Result<LuaTable> {
+        let event_data = self.lua.create_table().map_err(PluginError::LuaError)?;
+        event_data
+            .set("type", event.event_type.as_str())
+            .map_err(PluginError::LuaError)?;
+
+        match &event.data {
+            crate::types::EventData::Buffer {
+                document_id,
+                path,
+                revision,
+            } => {
+                event_data
+                    .set("document_id", format!("{:?}", document_id))
+                    .ok();
+                event_data
+                    .set(
+                        "path",
+                        path.as_ref().map(|p| p.to_string_lossy().to_string()),
+                    )
+                    .ok();
+                event_data.set("revision", *revision).ok();
+            }
+            crate::types::EventData::BufferChanged {
+                document_id,
+                revision,
+                start,
+                old_end,
+                new_end,
+            } => {
+                event_data
+                    .set("document_id", format!("{:?}", document_id))
+                    .ok();
+                event_data.set("revision", *revision).ok();
+                event_data.set("start", *start).ok();
+                event_data.set("old_end", *old_end).ok();
+                event_data.set("new_end", *new_end).ok();
+            }
+            crate::types::EventData::ModeChange { old_mode, new_mode } => {
+                event_data.set("old_mode", old_mode.clone()).ok();
+                event_data.set("new_mode", new_mode.clone()).ok();
+            }
+            crate::types::EventData::KeyPress { key } => {
+                event_data.set("key", key.clone()).ok();
+            }
+            crate::types::EventData::LspDiagnostic {
+                document_id,
+                diagnostic_count,
+            } => {
+                event_data
+                    .set("document_id", format!("{:?}", document_id))
+                    .ok();
+                event_data.set("diagnostic_count", *diagnostic_count).ok();
+            }
+            _ => {}
+        }
+
+        Ok(event_data)
+    }
+
     /// Call all event handlers for a given event
     pub fn call_event_handlers(&self, editor: &mut Editor, event: &PluginEvent) -> Result<()> {
         let handlers = self.event_handlers.read();
@@ -412,52 +985,19 @@ impl LuaEngine {
                     }
                 })?;
 
-                // Call the callback with event data
-                let event_data = self.lua.create_table().map_err(PluginError::LuaError)?;
-                event_data
-                    .set("type", event.event_type.as_str())
-                    .map_err(PluginError::LuaError)?;
-
-                // Set event-specific data
-                match &event.data {
-                    crate::types::EventData::Buffer { document_id, path } => {
-                        event_data
-                            .set("document_id", format!("{:?}", document_id))
-                            .ok();
-                        event_data
-                            .set(
-                                "path",
-                                path.as_ref().map(|p| p.to_string_lossy().to_string()),
-                            )
-                            .ok();
-                    }
-                    crate::types::EventData::ModeChange { old_mode, new_mode } => {
-                        event_data.set("old_mode", old_mode.clone()).ok();
-                        event_data.set("new_mode", new_mode.clone()).ok();
-                    }
-                    crate::types::EventData::KeyPress { key } => {
-                        event_data.set("key", key.clone()).ok();
-                    }
-                    crate::types::EventData::LspDiagnostic {
-                        document_id,
-                        diagnostic_count,
-                    } => {
-                        event_data
-                            .set("document_id", format!("{:?}", document_id))
-                            .ok();
-                        event_data.set("diagnostic_count", *diagnostic_count).ok();
-                    }
-                    _ => {}
-                }
+                let event_data = self.event_data_table(event)?;
 
                 let plugin_name_captured = plugin_name.clone();
-                with_editor_context(editor, || {
-                    callback
-                        .call::<()>(event_data)
-                        .map_err(|e| PluginError::EventHandlerError {
-                            plugin: plugin_name_captured,
-                            error: format!("Handler execution failed: {}", e),
+                self.reset_budget();
+                self.with_plugin_context(plugin_name, || {
+                    with_editor_context(editor, || {
+                        callback.call::<()>(event_data).map_err(|e| {
+                            Self::map_callback_error(e, |msg| PluginError::EventHandlerError {
+                                plugin: plugin_name_captured,
+                                error: format!("Handler execution failed: {}", msg),
+                            })
                         })
+                    })
                 })?;
             }
         }
@@ -465,6 +1005,347 @@ impl LuaEngine {
         Ok(())
     }
 
+    /// Call only `plugin_name`'s handlers for `event`. Used by
+    /// `PluginManager::reload_plugin` to fire `OnInit` for just the plugin
+    /// that was reloaded, rather than re-notifying every plugin that
+    /// happens to listen for it.
+    pub fn call_event_handlers_for_plugin(
+        &self,
+        editor: &mut Editor,
+        event: &PluginEvent,
+        plugin_name: &str,
+    ) -> Result<()> {
+        let handlers = self.event_handlers.read();
+        let Some(callbacks) = handlers.get(&event.event_type) else {
+            return Ok(());
+        };
+
+        for (name, callback_ref) in callbacks.iter().filter(|(name, _)| name == plugin_name) {
+            let callback: LuaFunction =
+                self.lua
+                    .registry_value(callback_ref)
+                    .map_err(|e| PluginError::EventHandlerError {
+                        plugin: name.clone(),
+                        error: format!("Failed to retrieve callback: {}", e),
+                    })?;
+
+            let event_data = self.event_data_table(event)?;
+
+            self.reset_budget();
+            self.with_plugin_context(plugin_name, || {
+                with_editor_context(editor, || {
+                    callback.call::<()>(event_data).map_err(|e| {
+                        Self::map_callback_error(e, |msg| PluginError::EventHandlerError {
+                            plugin: name.clone(),
+                            error: format!("Handler execution failed: {}", msg),
+                        })
+                    })
+                })
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Tear down exactly `name`'s registrations: its built-in and custom
+    /// (`helix.on` with a non-`EventType` name) event handlers, the
+    /// commands and UI callbacks it registered, its capability grants, any
+    /// `register_command_async` calls of its still in flight, the modules
+    /// its `require` has cached, and its entry in `plugins` - freeing each
+    /// removed `RegistryKey` via `remove_registry_value` so the Lua registry
+    /// doesn't accumulate callbacks that no plugin can reach anymore.
+    /// Leaves every other loaded plugin untouched, so this is safe to call
+    /// while the editor keeps running.
+    pub fn unload_plugin(&mut self, name: &str) -> Result<()> {
+        for handlers in self.event_handlers.write().values_mut() {
+            let mut i = 0;
+            while i < handlers.len() {
+                if handlers[i].0 == name {
+                    let (_, callback_ref) = handlers.remove(i);
+                    self.lua.remove_registry_value(callback_ref).ok();
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        for handlers in self.custom_handlers.write().values_mut() {
+            let mut i = 0;
+            while i < handlers.len() {
+                if handlers[i].0 == name {
+                    let (_, callback_ref) = handlers.remove(i);
+                    self.lua.remove_registry_value(callback_ref).ok();
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        if let Some(command_names) = self.registered_commands.write().remove(name) {
+            let mut commands = self.commands.write();
+            let mut async_commands = self.async_commands.write();
+            for command_name in command_names {
+                if let Some((_, callback_ref)) = commands.remove(&command_name) {
+                    self.lua.remove_registry_value(callback_ref).ok();
+                }
+                async_commands.remove(&command_name);
+            }
+        }
+
+        self.async_tasks.lock().retain(|task| task.plugin_name != name);
+
+        let mut module_cache = self.module_cache.write();
+        let stale_modules: Vec<(String, String)> = module_cache
+            .keys()
+            .filter(|(plugin_name, _)| plugin_name == name)
+            .cloned()
+            .collect();
+        for key in stale_modules {
+            if let Some(module_ref) = module_cache.remove(&key) {
+                self.lua.remove_registry_value(module_ref).ok();
+            }
+        }
+        drop(module_cache);
+
+        let mut ui_callbacks = self.ui_callbacks.write();
+        let stale_keys: Vec<(String, u64)> = ui_callbacks
+            .keys()
+            .filter(|(plugin_name, _)| plugin_name == name)
+            .cloned()
+            .collect();
+        for key in stale_keys {
+            if let Some(callback_ref) = ui_callbacks.remove(&key) {
+                self.lua.remove_registry_value(callback_ref).ok();
+            }
+        }
+        drop(ui_callbacks);
+
+        self.capabilities.write().remove(name);
+        self.plugins.remove(name);
+
+        if let Some(panel) = self.panels.write().remove(name) {
+            free_panel_node(&self.lua, panel.layout);
+        }
+
+        Ok(())
+    }
+
+    /// Reload `name` from disk: unload it, then re-run `load_plugin` from
+    /// its previously recorded [`crate::types::Plugin::path`]. Lets a
+    /// plugin be iterated on (`:plugin-reload <name>`) without restarting
+    /// Helix and without leaking the registry slots its previous load
+    /// claimed.
+    pub fn reload_plugin(&mut self, name: &str) -> Result<()> {
+        let plugin = self
+            .plugins
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PluginError::PluginNotFound(name.to_string()))?;
+
+        self.unload_plugin(name)?;
+        self.load_plugin(plugin)
+    }
+
+    /// Drain results produced off-thread by `helix.async`/async buffer
+    /// methods and apply them to the editor, then poll every in-flight
+    /// `register_command_async` call ([`AsyncTask`]) one step forward.
+    /// Call this once per editor tick.
+    pub fn poll_async(&self, editor: &mut Editor) -> Result<()> {
+        let ops: Vec<scheduler::PendingAsyncOp> = self.pending_async.lock().drain(..).collect();
+        for op in ops {
+            match op {
+                scheduler::PendingAsyncOp::ApplyTransaction {
+                    document_id,
+                    transaction,
+                } => {
+                    if let Some(doc) = editor.documents.get_mut(&document_id) {
+                        if let Some(view_id) = editor.tree.views().find_map(|(view, _)| {
+                            (view.doc == document_id).then_some(view.id)
+                        }) {
+                            doc.apply(&transaction, view_id);
+                        }
+                    }
+                }
+                scheduler::PendingAsyncOp::Callback {
+                    callback,
+                    result,
+                    plugin_name,
+                } => {
+                    let callback: LuaFunction =
+                        self.lua.registry_value(&callback).map_err(PluginError::LuaError)?;
+                    self.reset_budget();
+                    self.with_plugin_context(&plugin_name, || {
+                        with_editor_context(editor, || -> Result<()> {
+                            match result {
+                                Ok(value) => callback.call::<()>(value).map_err(PluginError::LuaError)?,
+                                Err(err) => callback
+                                    .call::<()>((LuaValue::Nil, err))
+                                    .map_err(PluginError::LuaError)?,
+                            }
+                            Ok(())
+                        })
+                    })?;
+                }
+                scheduler::PendingAsyncOp::WorkspaceRemoteOp { document_id, op } => {
+                    api::workspace::with_workspace_state(&self.lua, |state| match op {
+                        api::workspace::RemoteOp::Insert(insert) => {
+                            state.merge_insert(editor, document_id, insert)
+                        }
+                        api::workspace::RemoteOp::Delete(delete) => {
+                            state.merge_delete(editor, document_id, delete)
+                        }
+                        api::workspace::RemoteOp::Cursor { peer, char_idx } => {
+                            state.set_peer_cursor(document_id, peer, char_idx);
+                            state.render_peer_cursors(editor, document_id);
+                        }
+                    })
+                    .map_err(PluginError::LuaError)?;
+                }
+            }
+        }
+
+        let mut tasks = std::mem::take(&mut *self.async_tasks.lock());
+        let mut still_pending = Vec::with_capacity(tasks.len());
+        for mut task in tasks.drain(..) {
+            let plugin_name = task.plugin_name.clone();
+            // A resume is a fresh callback invocation in its own right - an
+            // AsyncTask suspended on e.g. `helix.timer` may be resumed many
+            // ticks after its budget was first set at submission, and
+            // without resetting here it would trip the wall-clock deadline
+            // (or accumulate instructions_used) from a window that has
+            // nothing to do with how long *this* resume actually takes.
+            self.reset_budget();
+            match self.with_plugin_context(&plugin_name, || self.drive_async_task(editor, &mut task.future)) {
+                Poll::Pending => still_pending.push(task),
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => log::warn!(
+                    "Async command '{}' in plugin '{}' failed: {}",
+                    task.command_name,
+                    task.plugin_name,
+                    e
+                ),
+            }
+        }
+        *self.async_tasks.lock() = still_pending;
+
+        Ok(())
+    }
+
+    /// Re-invoke every cached panel's `render` callbacks against the
+    /// current dock area and hand the refreshed widgets to the UI handler.
+    /// Not wired to an automatic redraw loop - callers decide when `needs_redraw`
+    /// actually warrants recomputing panel content.
+    pub fn redraw_panels(&self, editor: &mut Editor) -> Result<()> {
+        let Some(ref handler) = self.ui_handler else {
+            return Ok(());
+        };
+
+        let mut panels = self.panels.write();
+        for (plugin_name, panel) in panels.iter_mut() {
+            let area = api::dock_rect(editor.tree.area(), panel.dock, panel.size);
+            let widgets = api::render_panel(&self.lua, area, &panel.layout)
+                .map_err(PluginError::LuaError)?;
+            panel.widgets = widgets.clone();
+            handler.create_panel(editor, plugin_name.clone(), panel.dock, widgets);
+        }
+
+        Ok(())
+    }
+
+    /// Notify `buffer:on_change` subscribers of `document_id` that the char
+    /// range `start..end` was replaced with `new_text`. Call from the main
+    /// loop once the change has been applied.
+    pub fn fire_buffer_change(
+        &self,
+        editor: &mut Editor,
+        document_id: helix_view::DocumentId,
+        start: usize,
+        end: usize,
+        new_text: &str,
+    ) -> Result<()> {
+        let callbacks = self
+            .buffer_callbacks
+            .read()
+            .on_change
+            .get(&document_id)
+            .cloned()
+            .unwrap_or_default();
+        for callback_ref in &callbacks {
+            let callback: LuaFunction = self.lua.registry_value(callback_ref).map_err(PluginError::LuaError)?;
+            self.reset_budget();
+            with_editor_context(editor, || {
+                callback
+                    .call::<()>((start, end, new_text))
+                    .map_err(PluginError::LuaError)
+            })?;
+        }
+
+        // Stream the edit to any joined workspace transport (a no-op if this
+        // document was never synced via `helix.workspace.join`).
+        api::workspace::with_workspace_state(&self.lua, |state| {
+            state.forward_local_change(document_id, start, end, new_text)
+        })
+        .map_err(PluginError::LuaError)?;
+
+        Ok(())
+    }
+
+    /// Notify `buffer:on_cursor_move` subscribers that the primary cursor in
+    /// `document_id` moved to `char_idx`.
+    pub fn fire_cursor_move(
+        &self,
+        editor: &mut Editor,
+        document_id: helix_view::DocumentId,
+        char_idx: usize,
+    ) -> Result<()> {
+        let callbacks = self
+            .buffer_callbacks
+            .read()
+            .on_cursor_move
+            .get(&document_id)
+            .cloned()
+            .unwrap_or_default();
+        for callback_ref in &callbacks {
+            let callback: LuaFunction = self.lua.registry_value(callback_ref).map_err(PluginError::LuaError)?;
+            self.reset_budget();
+            with_editor_context(editor, || {
+                callback.call::<()>(char_idx).map_err(PluginError::LuaError)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Notify `buffer:on_diagnostics` subscribers that diagnostics for
+    /// `document_id` were refreshed, passing the current `LuaDiagnostic` list.
+    pub fn fire_diagnostics(&self, editor: &mut Editor, document_id: helix_view::DocumentId) -> Result<()> {
+        let callbacks = self
+            .buffer_callbacks
+            .read()
+            .on_diagnostics
+            .get(&document_id)
+            .cloned()
+            .unwrap_or_default();
+        if callbacks.is_empty() {
+            return Ok(());
+        }
+        let diagnostics: Vec<api::buffer::LuaDiagnostic> = editor
+            .document(document_id)
+            .map(|doc| doc.diagnostics().iter().cloned().map(Into::into).collect())
+            .unwrap_or_default();
+        for callback_ref in &callbacks {
+            let callback: LuaFunction = self.lua.registry_value(callback_ref).map_err(PluginError::LuaError)?;
+            let table = self.lua.create_table().map_err(PluginError::LuaError)?;
+            for (i, diag) in diagnostics.iter().enumerate() {
+                table.set(i + 1, diag.clone()).map_err(PluginError::LuaError)?;
+            }
+            self.reset_budget();
+            with_editor_context(editor, || {
+                callback.call::<()>(table.clone()).map_err(PluginError::LuaError)
+            })?;
+        }
+        Ok(())
+    }
+
     /// Get the Lua runtime (for advanced operations)
     pub fn lua(&self) -> &Lua {
         &self.lua
@@ -476,6 +1357,72 @@ impl LuaEngine {
     }
 }
 
+impl crate::runtime::PluginRuntime for LuaEngine {
+    fn handles(&self, plugin: &crate::types::Plugin) -> bool {
+        !plugin
+            .metadata
+            .entry
+            .as_deref()
+            .unwrap_or("")
+            .ends_with(".wasm")
+    }
+
+    fn register_api(&self, config: crate::types::PluginConfig) -> Result<()> {
+        LuaEngine::register_api(self, config)
+    }
+
+    fn load_plugin(&mut self, plugin: crate::types::Plugin) -> Result<()> {
+        LuaEngine::load_plugin(self, plugin)
+    }
+
+    fn unload_plugin(&mut self, name: &str) -> Result<()> {
+        LuaEngine::unload_plugin(self, name)
+    }
+
+    fn call_event_handlers(&self, editor: &mut Editor, event: &PluginEvent) -> Result<()> {
+        LuaEngine::call_event_handlers(self, editor, event)
+    }
+
+    fn call_event_handlers_for_plugin(
+        &self,
+        editor: &mut Editor,
+        event: &PluginEvent,
+        plugin_name: &str,
+    ) -> Result<()> {
+        LuaEngine::call_event_handlers_for_plugin(self, editor, event, plugin_name)
+    }
+
+    fn execute_command(&self, editor: &mut Editor, name: &str, args: Vec<String>) -> Result<()> {
+        LuaEngine::execute_command(self, editor, name, args)
+    }
+
+    fn handle_ui_callback(
+        &self,
+        editor: &mut Editor,
+        plugin_name: String,
+        callback_id: u64,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        LuaEngine::handle_ui_callback(self, editor, plugin_name, callback_id, value)
+    }
+
+    fn get_commands(&self) -> Vec<crate::types::CommandMetadata> {
+        LuaEngine::get_commands(self)
+    }
+
+    fn get_commands_for_plugin(&self, plugin_name: &str) -> Vec<crate::types::CommandMetadata> {
+        LuaEngine::get_commands_for_plugin(self, plugin_name)
+    }
+
+    fn plugins(&self) -> Vec<crate::types::Plugin> {
+        LuaEngine::plugins(self).values().cloned().collect()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
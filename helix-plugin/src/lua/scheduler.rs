@@ -0,0 +1,84 @@
+//! Background async scheduler for plugin callbacks.
+//!
+//! Modeled on codemp's `Driver`: the editor state (`&mut Editor`) is not
+//! `Send` and must stay on the main thread, so async plugin work only ever
+//! spawns the *non-Lua* part of a future (an HTTP request, a subprocess, a
+//! timer) on a shared background tokio runtime. The result is marshaled back
+//! as a [`PendingAsyncOp`] and applied to the editor from the main loop on
+//! the next tick via [`crate::lua::LuaEngine::poll_async`].
+
+use std::sync::OnceLock;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+static RUNTIME: OnceLock<Arc<Runtime>> = OnceLock::new();
+
+/// Lazily start (or reuse) the shared background runtime that drives plugin
+/// futures off the main thread.
+pub(crate) fn background_runtime() -> Arc<Runtime> {
+    RUNTIME
+        .get_or_init(|| {
+            Arc::new(
+                tokio::runtime::Builder::new_multi_thread()
+                    .thread_name("hx-plugin-async")
+                    .enable_all()
+                    .build()
+                    .expect("failed to start plugin async runtime"),
+            )
+        })
+        .clone()
+}
+
+/// Lua-facing handle to a spawned background task, returned by
+/// `helix.async.spawn`. Dropping the handle does not cancel the task;
+/// scripts must call `stop()` explicitly.
+pub struct Driver {
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Driver {
+    pub(crate) fn new(handle: JoinHandle<()>) -> Self {
+        Self {
+            handle: Some(handle),
+        }
+    }
+}
+
+impl mlua::UserData for Driver {
+    fn add_methods<M: mlua::UserDataMethods<Self>>(methods: &mut M) {
+        // driver:stop() - abort the task if it hasn't completed yet
+        methods.add_method_mut("stop", |_, this, ()| {
+            if let Some(handle) = this.handle.take() {
+                handle.abort();
+            }
+            Ok(())
+        });
+    }
+}
+
+/// A result produced off the main thread that still needs to be applied to
+/// the editor (or handed to a Lua callback) from the main loop.
+pub enum PendingAsyncOp {
+    /// Apply a transaction to a document once the editor tick picks it up.
+    ApplyTransaction {
+        document_id: helix_view::DocumentId,
+        transaction: helix_core::Transaction,
+    },
+    /// Invoke a plugin-supplied completion callback with the resolved value.
+    Callback {
+        callback: mlua::RegistryKey,
+        result: Result<String, String>,
+        /// The plugin that called `helix.async.spawn`, captured at spawn
+        /// time so `LuaEngine::poll_async` can restore `_current_plugin_name`
+        /// around the callback instead of it reading as "unknown".
+        plugin_name: String,
+    },
+    /// A CRDT op (or cursor update) received over a joined workspace's
+    /// transport, to be merged into the synced document's `WorkspaceState`
+    /// from the main loop. See `crate::lua::api::workspace::join`.
+    WorkspaceRemoteOp {
+        document_id: helix_view::DocumentId,
+        op: crate::lua::api::workspace::RemoteOp,
+    },
+}
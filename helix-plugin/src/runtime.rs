@@ -0,0 +1,58 @@
+use crate::error::Result;
+use crate::types::{CommandMetadata, Plugin, PluginConfig, PluginEvent};
+use helix_view::Editor;
+
+/// Abstracts over a plugin execution backend, so `PluginManager` can run
+/// plugins written for different runtimes side by side through the same
+/// event/command model. [`crate::lua::LuaEngine`] and
+/// [`crate::wasm::WasmEngine`] both implement this; `PluginManager` holds
+/// one of each in `runtimes` and fans event/command calls out across all of
+/// them rather than hard-coding Lua as the only option.
+pub trait PluginRuntime: Send + Sync {
+    /// Whether this runtime is the right one to load `plugin`, judged by
+    /// its entry point (e.g. an `entry` ending in `.wasm` vs `.lua`).
+    fn handles(&self, plugin: &Plugin) -> bool;
+
+    /// Wire up this runtime's API surface against `config` (once, before
+    /// any plugin loads).
+    fn register_api(&self, config: PluginConfig) -> Result<()>;
+
+    fn load_plugin(&mut self, plugin: Plugin) -> Result<()>;
+
+    /// Tear down `name`'s registrations on this runtime. A no-op if `name`
+    /// isn't loaded here.
+    fn unload_plugin(&mut self, name: &str) -> Result<()>;
+
+    fn call_event_handlers(&self, editor: &mut Editor, event: &PluginEvent) -> Result<()>;
+
+    /// Call only `plugin_name`'s handlers for `event`, for reload's
+    /// single-plugin `OnInit`. A no-op if `plugin_name` isn't loaded here.
+    fn call_event_handlers_for_plugin(
+        &self,
+        editor: &mut Editor,
+        event: &PluginEvent,
+        plugin_name: &str,
+    ) -> Result<()>;
+
+    fn execute_command(&self, editor: &mut Editor, name: &str, args: Vec<String>) -> Result<()>;
+
+    fn handle_ui_callback(
+        &self,
+        editor: &mut Editor,
+        plugin_name: String,
+        callback_id: u64,
+        value: serde_json::Value,
+    ) -> Result<()>;
+
+    fn get_commands(&self) -> Vec<CommandMetadata>;
+
+    /// Commands `plugin_name` registered, for the plugin metadata cache.
+    fn get_commands_for_plugin(&self, plugin_name: &str) -> Vec<CommandMetadata>;
+
+    /// Every plugin currently loaded on this runtime.
+    fn plugins(&self) -> Vec<Plugin>;
+
+    /// Poor man's downcast, so `PluginManager::runtime::<T>()` can reach a
+    /// backend-specific method not part of this trait.
+    fn as_any(&self) -> &dyn std::any::Any;
+}
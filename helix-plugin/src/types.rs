@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 /// Represents a plugin event type that can be subscribed to
@@ -92,10 +93,23 @@ pub struct PluginEvent {
 pub enum EventData {
     /// No data
     None,
-    /// Buffer-related data
+    /// Buffer-related data (open/pre-save/post-save/close)
     Buffer {
         document_id: helix_view::DocumentId,
         path: Option<PathBuf>,
+        /// The document's revision at the time the event fired, so a plugin
+        /// caching by revision can tell whether it's already seen this state.
+        revision: u64,
+    },
+    /// `OnBufferChanged` data: the char range that was replaced, rather than
+    /// just "something changed". A plugin can diff `start..old_end` against
+    /// its cached text instead of re-fetching the whole buffer.
+    BufferChanged {
+        document_id: helix_view::DocumentId,
+        revision: u64,
+        start: usize,
+        old_end: usize,
+        new_end: usize,
     },
     /// Mode change data
     ModeChange { old_mode: String, new_mode: String },
@@ -113,6 +127,20 @@ pub enum EventData {
     },
 }
 
+/// A privileged operation a plugin may be granted access to. Each variant
+/// gates one `helix` submodule, which a plugin can call only if its
+/// `plugin.toml` lists that capability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Persistent key-value storage (`helix.storage`)
+    Storage,
+    /// Collaborative workspace / network access (`helix.workspace`)
+    Workspace,
+    /// Language server interaction (`helix.lsp`)
+    Lsp,
+}
+
 /// Plugin metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
@@ -126,6 +154,13 @@ pub struct PluginMetadata {
     pub author: Option<String>,
     /// Plugin entry point (default: init.lua)
     pub entry: Option<String>,
+    /// Capabilities this plugin is allowed to use. Any capability not named
+    /// here is denied. Only `helix.storage`, `helix.workspace` and
+    /// `helix.lsp` are gated this way: the rest of the `helix` API, buffer
+    /// edits and `helix.editor` commands included, is available to every
+    /// plugin, and the `helix` table is not made read-only.
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
 }
 
 impl Default for PluginMetadata {
@@ -136,12 +171,13 @@ impl Default for PluginMetadata {
             description: None,
             author: None,
             entry: Some("init.lua".to_string()),
+            capabilities: Vec::new(),
         }
     }
 }
 
 /// Represents a loaded plugin
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Plugin {
     /// Plugin metadata
     pub metadata: PluginMetadata,
@@ -163,6 +199,9 @@ pub struct PluginConfig {
     /// Individual plugin configurations
     #[serde(default)]
     pub plugins: Vec<IndividualPluginConfig>,
+    /// Resource limits enforced on every plugin callback
+    #[serde(default)]
+    pub limits: PluginLimits,
 }
 
 fn default_true() -> bool {
@@ -175,6 +214,47 @@ impl Default for PluginConfig {
             enabled: true,
             plugin_dirs: vec![],
             plugins: vec![],
+            limits: PluginLimits::default(),
+        }
+    }
+}
+
+/// Resource budget enforced on a single plugin callback invocation (an
+/// event handler, a command, or a UI callback), via an mlua debug hook plus
+/// `Lua::set_memory_limit`. Defaults are generous enough not to bother a
+/// well-behaved plugin, but catch a `while true do end` or a runaway
+/// allocation before it freezes the editor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginLimits {
+    /// Wall-clock budget for one callback invocation, in milliseconds
+    #[serde(default = "default_max_duration_ms")]
+    pub max_duration_ms: u64,
+    /// Lua instructions one callback invocation may execute
+    #[serde(default = "default_max_instructions")]
+    pub max_instructions: u64,
+    /// Heap bytes the Lua runtime may allocate in total
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: usize,
+}
+
+fn default_max_duration_ms() -> u64 {
+    200
+}
+
+fn default_max_instructions() -> u64 {
+    50_000_000
+}
+
+fn default_max_memory_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            max_duration_ms: default_max_duration_ms(),
+            max_instructions: default_max_instructions(),
+            max_memory_bytes: default_max_memory_bytes(),
         }
     }
 }
@@ -182,7 +262,9 @@ impl Default for PluginConfig {
 /// Configuration for an individual plugin
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndividualPluginConfig {
-    /// Plugin name
+    /// Plugin name. Matched against a directory-discovered plugin's own
+    /// metadata name when `source` is unset; otherwise just a label for
+    /// this config entry (`alias` decides the identity plugins load under).
     pub name: String,
     /// Whether this plugin is enabled
     #[serde(default = "default_true")]
@@ -190,6 +272,28 @@ pub struct IndividualPluginConfig {
     /// Plugin-specific configuration
     #[serde(default)]
     pub config: serde_json::Value,
+    /// Short handle other commands/event subscriptions should use to refer
+    /// to this plugin instance instead of its discovered name. Lets
+    /// `source` be loaded twice under two names with two `config` blobs,
+    /// zellij-`RunPluginOrAlias` style.
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Explicit plugin location: an absolute/relative path, or a bare name
+    /// resolved against the configured plugin directories. When set, this
+    /// entry loads from `source` directly instead of requiring a plugin by
+    /// `name` to already be in the auto-discovered set.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl IndividualPluginConfig {
+    /// The identity this config entry's plugin loads under: `alias` if
+    /// set, otherwise `name`. Event handlers, commands, and `_current_plugin_name`
+    /// are all keyed by this, so config lookups must match it rather than
+    /// `name` alone once an alias is in play.
+    pub fn effective_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
 }
 
 /// Metadata for a registered command
@@ -216,6 +320,54 @@ pub trait EditorCommandRegistry: Send + Sync {
 /// Wrapper for EditorCommandRegistry to store in Lua app data
 pub struct CommandRegistryWrapper(pub std::sync::Arc<dyn EditorCommandRegistry>);
 
+/// A single row for `helix.ui.picker`: a displayed `label` (with optional
+/// `secondary` text and extra `columns`, matching Helix's own column-picker
+/// layout) plus an opaque `id` returned to the plugin on selection, so a row
+/// can show one thing and select another. `data` carries the original Lua
+/// value a plugin passed in (converted to JSON), returned verbatim to
+/// `on_select`/`on_preview`/`on_filter` alongside `id` - `id`/`label` are
+/// enough to draw the row, but a plugin building "open recent", "symbols",
+/// or "git status" off structured data shouldn't have to re-derive it from
+/// display strings.
+#[derive(Debug, Clone)]
+pub struct PickerItem {
+    pub id: String,
+    pub label: String,
+    pub secondary: Option<String>,
+    pub columns: Vec<String>,
+    pub data: serde_json::Value,
+}
+
+/// Column-header/width/fuzzy-filter metadata for a `helix.ui.picker` call,
+/// broken out of `UiHandler::picker`'s argument list since `helix.ui.picker`
+/// grew a columnar-table mode (`column_names`/`column_widths`) and a
+/// pluggable matcher (`filter_column`/`filter_callback_id`) on top of its
+/// original flat-list form.
+#[derive(Debug, Clone, Default)]
+pub struct PickerOptions {
+    pub prompt: String,
+    /// When set, the callback receives a JSON array of the selected items'
+    /// `data` instead of stopping at the first pick.
+    pub multi_select: bool,
+    /// Header row for items' `columns`. Empty when the picker isn't using
+    /// column mode.
+    pub column_names: Vec<String>,
+    /// Per-column width constraint, aligned with `column_names`/each item's
+    /// `columns` (`0` means size to content).
+    pub column_widths: Vec<u16>,
+    /// Which column (0-based, into an item's `columns`) the built-in fuzzy
+    /// matcher filters against; `None` matches `label` as today.
+    pub filter_column: Option<usize>,
+    /// A plugin-supplied match function, invoked with the current query and
+    /// looked up via `UiCallbackRegistry`, overriding the built-in fuzzy
+    /// matcher entirely - set by `helix.ui.picker`'s `on_filter`.
+    pub filter_callback_id: Option<u64>,
+    /// Invoked with the currently-highlighted item's `data` as the
+    /// selection moves, so plugins can render a live preview the way
+    /// Helix's own pickers do.
+    pub preview_callback_id: Option<u64>,
+}
+
 /// Interface for handling UI elements (prompts, pickers) that require compositor access
 pub trait UiHandler: Send + Sync {
     fn prompt(
@@ -233,14 +385,171 @@ pub trait UiHandler: Send + Sync {
         plugin_name: String,
         callback_id: u64,
     );
+    /// Show a fuzzy-matching picker over `items`, configured by `options`
+    /// (prompt text, multi-select, column headers/widths, and filter/preview
+    /// callbacks - see [`PickerOptions`]).
     fn picker(
         &self,
         editor: &mut helix_view::Editor,
-        items: Vec<String>,
+        items: Vec<PickerItem>,
+        options: PickerOptions,
+        plugin_name: String,
+        callback_id: u64,
+    );
+    /// Show an editable input field anchored over `anchor` in the buffer,
+    /// rather than at the bottom of the screen. The callback receives both
+    /// the typed text and `anchor`, enabling context-local interactions
+    /// like "rewrite this selection" without losing sight of the target
+    /// code.
+    fn inline_input(
+        &self,
+        editor: &mut helix_view::Editor,
+        anchor: InlineInputAnchor,
         prompt: String,
+        default: Option<String>,
         plugin_name: String,
         callback_id: u64,
     );
+    /// Display (or refresh) `plugin_name`'s docked panel: `widgets` are
+    /// this pass's resolved leaves, already laid out against the docked
+    /// area by `helix.ui.create_panel`/`LuaEngine::redraw_panels`.
+    fn create_panel(
+        &self,
+        editor: &mut helix_view::Editor,
+        plugin_name: String,
+        dock: DockSide,
+        widgets: Vec<ResolvedPanelWidget>,
+    );
+    /// Show a transient floating popover anchored to `resolved.anchor` -
+    /// hover docs, signature help, or plugin diagnostics. `resolved.area`
+    /// is already placed (above or below the anchor, whichever has room)
+    /// and clamped to stay inside the viewport, so the impl only needs to
+    /// draw `resolved.lines` inside it. Per `helix.ui.popover`'s contract,
+    /// the impl must dismiss the popover on the next cursor move or
+    /// keypress rather than waiting for an explicit close call.
+    fn popover(
+        &self,
+        editor: &mut helix_view::Editor,
+        plugin_name: String,
+        resolved: ResolvedPopover,
+    );
+}
+
+/// Which edge of the screen a `helix.ui.create_panel` panel is docked
+/// against. The crate only carves a `size`-wide/tall strip off this edge -
+/// anything fancier (floating, centered) is out of scope for a docked
+/// sidebar/status-panel API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockSide {
+    Left,
+    Right,
+    Bottom,
+}
+
+/// A `helix.ui.create_panel` layout constraint, same three kinds ratatui's
+/// `Layout` offers - this crate doesn't depend on ratatui directly, so
+/// `LuaEngine`'s own `lua::api::ui::split_rect` resolves these itself
+/// rather than reusing `tui::layout::Constraint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelConstraint {
+    Percentage(u16),
+    Length(u16),
+    Min(u16),
+}
+
+/// The axis a `PanelNode::Split` divides its area along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// What a `PanelNode::Widget` leaf renders as - purely a hint for the
+/// `UiHandler` impl that actually draws it; this crate doesn't interpret
+/// `content` differently per kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelWidgetType {
+    List,
+    Table,
+    Paragraph,
+}
+
+/// A node in a `helix.ui.create_panel` layout tree: either a `Split` that
+/// divides its resolved area among `children` by `PanelConstraint`, or a
+/// `Widget` leaf whose content comes from calling its `render` callback.
+pub enum PanelNode {
+    Split {
+        direction: PanelDirection,
+        children: Vec<(PanelConstraint, PanelNode)>,
+    },
+    Widget {
+        widget_type: PanelWidgetType,
+        render: mlua::RegistryKey,
+    },
+}
+
+/// One resolved `PanelNode::Widget` leaf: the screen area it was laid out
+/// into, and its rendered content - whatever JSON value its `render`
+/// callback returned (rows for `list`/`table`, spans for `paragraph`) -
+/// ready to hand to a `UiHandler` for display.
+#[derive(Debug, Clone)]
+pub struct ResolvedPanelWidget {
+    pub area: helix_view::graphics::Rect,
+    pub widget_type: PanelWidgetType,
+    pub content: serde_json::Value,
+}
+
+/// A plugin's panel, cached by plugin name so `LuaEngine::redraw_panels`
+/// can re-invoke its `render` callbacks without the plugin re-declaring
+/// the layout on every redraw.
+pub struct CachedPanel {
+    pub dock: DockSide,
+    pub size: u16,
+    pub layout: PanelNode,
+    pub widgets: Vec<ResolvedPanelWidget>,
+}
+
+/// Wrapper for the per-plugin panel cache to store in Lua app data.
+pub struct PanelRegistry(
+    pub std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<String, CachedPanel>>>,
+);
+
+/// One line of a `helix.ui.popover`'s content, already split into
+/// ANSI-styled runs (see [`helix_view::annotations::ansi`]) so the
+/// `UiHandler` impl can draw it without re-parsing escape sequences itself.
+pub type PopoverLine = Vec<helix_view::annotations::ansi::AnsiSpan>;
+
+/// Where a `helix.ui.popover` anchors in the buffer - the document and the
+/// char index its content (hover docs, signature help, diagnostics) concerns.
+#[derive(Debug, Clone, Copy)]
+pub struct PopoverAnchor {
+    pub document_id: helix_view::DocumentId,
+    pub char_idx: usize,
+}
+
+/// A resolved `helix.ui.popover`: `area` is already placed above or below
+/// `anchor` (whichever has room in the viewport) and clamped horizontally,
+/// with its height sized for `lines` soft-wrapped at its width (`lines`
+/// themselves are not pre-wrapped) - the `UiHandler` impl just draws
+/// a box there and dismisses it on the next cursor move or keypress.
+#[derive(Debug, Clone)]
+pub struct ResolvedPopover {
+    pub anchor: PopoverAnchor,
+    pub lines: Vec<PopoverLine>,
+    pub area: helix_view::graphics::Rect,
+    pub scrollable: bool,
+}
+
+/// Where an inline input overlay anchors in a buffer: the document and a
+/// char range, so the field renders directly against the code it concerns
+/// instead of floating at the bottom of the screen (Zed's inline-assist
+/// pattern). Delivered back to the plugin's callback alongside the typed
+/// text so it can act on exactly the range the input was anchored to.
+#[derive(Debug, Clone, Copy)]
+pub struct InlineInputAnchor {
+    pub document_id: helix_view::DocumentId,
+    pub start: usize,
+    pub end: usize,
 }
 
 /// Wrapper for UiHandler to store in Lua app data
@@ -255,3 +564,44 @@ pub struct UiCallbackRegistry(
 
 /// Wrapper for UI callback counter to store in Lua app data
 pub struct UiCallbackCounter(pub std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+/// Wrapper for the queue of background-scheduler results awaiting
+/// application on the main loop, stored in Lua app data.
+pub struct PendingAsyncOps(
+    pub std::sync::Arc<parking_lot::Mutex<Vec<crate::lua::scheduler::PendingAsyncOp>>>,
+);
+
+/// Per-plugin capability grants, keyed by plugin name, stored in Lua app
+/// data so gated API functions can check `_current_plugin_name` against it.
+pub struct CapabilityRegistry(
+    pub std::sync::Arc<parking_lot::RwLock<std::collections::HashMap<String, HashSet<Capability>>>>,
+);
+
+/// Look up whether the currently-executing plugin has been granted `cap`.
+/// Gated API functions (storage, workspace, lsp) call this before doing
+/// anything a malicious or buggy plugin could abuse.
+pub fn require_capability(lua: &mlua::Lua, cap: Capability) -> mlua::Result<()> {
+    let plugin_name = lua
+        .globals()
+        .get::<String>("_current_plugin_name")
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let registry = lua
+        .app_data_ref::<CapabilityRegistry>()
+        .ok_or_else(|| mlua::Error::RuntimeError("capability registry not installed".to_string()))?;
+
+    let granted = registry
+        .0
+        .read()
+        .get(&plugin_name)
+        .is_some_and(|caps| caps.contains(&cap));
+
+    if granted {
+        Ok(())
+    } else {
+        Err(mlua::Error::RuntimeError(format!(
+            "plugin '{}' does not have the '{:?}' capability (add it to plugin.toml's `capabilities` list)",
+            plugin_name, cap
+        )))
+    }
+}
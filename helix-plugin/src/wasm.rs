@@ -0,0 +1,331 @@
+//! WASM plugin backend.
+//!
+//! Runs `wasm32-wasi` modules as a sandboxed alternative to Lua for
+//! CPU-heavy or non-Lua plugins, following Zed's approach to running
+//! language servers through WASM. A module speaks a minimal, Extism-style
+//! ABI: it exports `alloc(len: i32) -> i32` plus one function per call
+//! (`handle_event`, `execute_command`, `get_commands`), each taking a
+//! `(ptr, len)` pointing at a JSON payload in the module's own linear
+//! memory and returning a packed `(out_ptr << 32) | out_len` i64 pointing
+//! at a JSON response in that same memory. There's no host-function import
+//! for `helix.*` yet, so unlike Lua plugins, a WASM plugin's `handle_event`
+//! and `execute_command` can't read or mutate the editor - only inspect the
+//! event/command payload it's handed. Wiring up host imports (starting
+//! with the `helix.ui.*` equivalent Lua plugins get through `UiHandler`) is
+//! tracked as follow-up work.
+
+use crate::error::{PluginError, Result};
+use crate::runtime::PluginRuntime;
+use crate::types::{CommandMetadata, EventData, Plugin, PluginConfig, PluginEvent};
+use helix_view::Editor;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use wasmtime::{Engine, Instance, Linker, Module, Store};
+use wasmtime_wasi::sync::WasiCtxBuilder;
+use wasmtime_wasi::WasiCtx;
+
+struct LoadedWasmPlugin {
+    plugin: Plugin,
+    store: Store<WasiCtx>,
+    instance: Instance,
+}
+
+/// Runs `.wasm` plugins alongside [`crate::lua::LuaEngine`]'s `.lua` ones.
+/// Each plugin gets its own `Store`/`Instance`, since `wasmtime::Store` is
+/// `Send` but not `Sync` - a `Mutex` lets the whole map stay `Sync` without
+/// requiring that of the stores it holds.
+pub struct WasmEngine {
+    engine: Engine,
+    plugins: Mutex<HashMap<String, LoadedWasmPlugin>>,
+    /// Command name -> (metadata, owning plugin name), gathered once from
+    /// each plugin's `get_commands` export at load time. Unlike Lua plugins,
+    /// which register commands one at a time via `helix.register_command`,
+    /// a WASM module just declares its whole command list up front.
+    commands: Mutex<HashMap<String, (CommandMetadata, String)>>,
+}
+
+impl WasmEngine {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            engine: Engine::default(),
+            plugins: Mutex::new(HashMap::new()),
+            commands: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn refresh_commands(&self, plugin_name: &str, store: &mut Store<WasiCtx>, instance: &Instance) {
+        match call_export(store, instance, "get_commands", &[]) {
+            Ok(bytes) if !bytes.is_empty() => match serde_json::from_slice::<Vec<CommandMetadata>>(&bytes) {
+                Ok(commands) => {
+                    let mut registry = self.commands.lock();
+                    for command in commands {
+                        registry.insert(command.name.clone(), (command, plugin_name.to_string()));
+                    }
+                }
+                Err(e) => {
+                    log::warn!("WASM plugin '{}' returned an invalid command list: {}", plugin_name, e)
+                }
+            },
+            Ok(_) => {}
+            Err(e) => log::warn!("WASM plugin '{}' failed to report its commands: {}", plugin_name, e),
+        }
+    }
+}
+
+/// Call `name` in `instance` with `payload` as its JSON argument, following
+/// the module's `alloc`/packed-pointer ABI. Returns an empty response if the
+/// module doesn't export `name` at all (every call except `alloc` itself is
+/// optional).
+fn call_export(
+    store: &mut Store<WasiCtx>,
+    instance: &Instance,
+    name: &str,
+    payload: &[u8],
+) -> Result<Vec<u8>> {
+    let Ok(func) = instance.get_typed_func::<(i32, i32), i64>(&mut *store, name) else {
+        return Ok(Vec::new());
+    };
+
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| PluginError::InvalidPluginStructure("WASM module has no exported memory".into()))?;
+
+    let ptr = if payload.is_empty() {
+        0
+    } else {
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut *store, "alloc")
+            .map_err(|e| {
+                PluginError::InvalidPluginStructure(format!("WASM module has no 'alloc' export: {}", e))
+            })?;
+        let ptr = alloc
+            .call(&mut *store, payload.len() as i32)
+            .map_err(|e| PluginError::InitializationFailed(format!("'alloc' call failed: {}", e)))?;
+        memory
+            .write(&mut *store, ptr as usize, payload)
+            .map_err(|e| PluginError::InitializationFailed(format!("Failed to write plugin input: {}", e)))?;
+        ptr
+    };
+
+    let packed = func
+        .call(&mut *store, (ptr, payload.len() as i32))
+        .map_err(|e| PluginError::InitializationFailed(format!("'{}' call failed: {}", name, e)))?;
+
+    let out_len = (packed & 0xffff_ffff) as u32 as usize;
+    if out_len == 0 {
+        return Ok(Vec::new());
+    }
+    let out_ptr = (packed >> 32) as u32 as usize;
+
+    let mut out = vec![0u8; out_len];
+    memory
+        .read(&*store, out_ptr, &mut out)
+        .map_err(|e| PluginError::InitializationFailed(format!("Failed to read plugin output: {}", e)))?;
+    Ok(out)
+}
+
+/// Render a [`PluginEvent`] as the JSON payload a WASM plugin's
+/// `handle_event` export receives. Kept separate from
+/// `LuaEngine::event_data_table`'s Lua-table version rather than sharing an
+/// intermediate representation - the two targets (a `mlua::Table` and a
+/// `serde_json::Value`) don't have enough in common to be worth unifying.
+fn event_to_json(event: &PluginEvent) -> serde_json::Value {
+    let mut data = serde_json::Map::new();
+    data.insert(
+        "type".to_string(),
+        serde_json::Value::String(event.event_type.as_str().to_string()),
+    );
+
+    match &event.data {
+        EventData::None => {}
+        EventData::Buffer {
+            document_id,
+            path,
+            revision,
+        } => {
+            data.insert("document_id".into(), format!("{:?}", document_id).into());
+            data.insert(
+                "path".into(),
+                path.as_ref()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .into(),
+            );
+            data.insert("revision".into(), (*revision).into());
+        }
+        EventData::BufferChanged {
+            document_id,
+            revision,
+            start,
+            old_end,
+            new_end,
+        } => {
+            data.insert("document_id".into(), format!("{:?}", document_id).into());
+            data.insert("revision".into(), (*revision).into());
+            data.insert("start".into(), (*start).into());
+            data.insert("old_end".into(), (*old_end).into());
+            data.insert("new_end".into(), (*new_end).into());
+        }
+        EventData::ModeChange { old_mode, new_mode } => {
+            data.insert("old_mode".into(), old_mode.clone().into());
+            data.insert("new_mode".into(), new_mode.clone().into());
+        }
+        EventData::KeyPress { key } => {
+            data.insert("key".into(), key.clone().into());
+        }
+        EventData::LspAttach {
+            document_id,
+            language_server_id,
+        } => {
+            data.insert("document_id".into(), format!("{:?}", document_id).into());
+            data.insert("language_server_id".into(), (*language_server_id).into());
+        }
+        EventData::LspDiagnostic {
+            document_id,
+            diagnostic_count,
+        } => {
+            data.insert("document_id".into(), format!("{:?}", document_id).into());
+            data.insert("diagnostic_count".into(), (*diagnostic_count).into());
+        }
+    }
+
+    serde_json::Value::Object(data)
+}
+
+impl PluginRuntime for WasmEngine {
+    fn handles(&self, plugin: &Plugin) -> bool {
+        plugin
+            .metadata
+            .entry
+            .as_deref()
+            .unwrap_or("")
+            .ends_with(".wasm")
+    }
+
+    fn register_api(&self, _config: PluginConfig) -> Result<()> {
+        // No host-function equivalent of `helix.*` is imported into the
+        // linker yet, so there's no per-config API surface to wire up here -
+        // see the module doc comment.
+        Ok(())
+    }
+
+    fn load_plugin(&mut self, plugin: Plugin) -> Result<()> {
+        let entry = plugin.metadata.entry.as_deref().unwrap_or("plugin.wasm");
+        let wasm_path = plugin.path.join(entry);
+        let bytes = std::fs::read(&wasm_path)?;
+
+        let module = Module::new(&self.engine, &bytes)
+            .map_err(|e| PluginError::InvalidPluginStructure(format!("Failed to compile WASM module: {}", e)))?;
+
+        let wasi = WasiCtxBuilder::new().inherit_stdio().build();
+        let mut store = Store::new(&self.engine, wasi);
+
+        let mut linker = Linker::new(&self.engine);
+        wasmtime_wasi::sync::add_to_linker(&mut linker, |ctx| ctx)
+            .map_err(|e| PluginError::InitializationFailed(format!("Failed to link WASI: {}", e)))?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| PluginError::InitializationFailed(format!("Failed to instantiate plugin: {}", e)))?;
+
+        let name = plugin.metadata.name.clone();
+        self.refresh_commands(&name, &mut store, &instance);
+
+        self.plugins.lock().insert(
+            name,
+            LoadedWasmPlugin {
+                plugin,
+                store,
+                instance,
+            },
+        );
+        Ok(())
+    }
+
+    fn unload_plugin(&mut self, name: &str) -> Result<()> {
+        self.plugins.lock().remove(name);
+        self.commands.lock().retain(|_, (_, owner)| owner != name);
+        Ok(())
+    }
+
+    fn call_event_handlers(&self, _editor: &mut Editor, event: &PluginEvent) -> Result<()> {
+        let payload = serde_json::to_vec(&event_to_json(event)).unwrap_or_default();
+        let mut plugins = self.plugins.lock();
+        for (name, loaded) in plugins.iter_mut() {
+            if let Err(e) = call_export(&mut loaded.store, &loaded.instance, "handle_event", &payload) {
+                log::warn!("WASM plugin '{}' failed handling event: {}", name, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn call_event_handlers_for_plugin(
+        &self,
+        _editor: &mut Editor,
+        event: &PluginEvent,
+        plugin_name: &str,
+    ) -> Result<()> {
+        let payload = serde_json::to_vec(&event_to_json(event)).unwrap_or_default();
+        let mut plugins = self.plugins.lock();
+        if let Some(loaded) = plugins.get_mut(plugin_name) {
+            call_export(&mut loaded.store, &loaded.instance, "handle_event", &payload)?;
+        }
+        Ok(())
+    }
+
+    fn execute_command(&self, _editor: &mut Editor, name: &str, args: Vec<String>) -> Result<()> {
+        let owner = self
+            .commands
+            .lock()
+            .get(name)
+            .map(|(_, owner)| owner.clone())
+            .ok_or_else(|| PluginError::CommandExecutionFailed(format!("Command not found: {}", name)))?;
+
+        let payload = serde_json::to_vec(&serde_json::json!({ "name": name, "args": args }))
+            .map_err(|e| PluginError::CommandExecutionFailed(e.to_string()))?;
+
+        let mut plugins = self.plugins.lock();
+        let loaded = plugins
+            .get_mut(&owner)
+            .ok_or_else(|| PluginError::CommandExecutionFailed(format!("Command not found: {}", name)))?;
+
+        call_export(&mut loaded.store, &loaded.instance, "execute_command", &payload)?;
+        Ok(())
+    }
+
+    fn handle_ui_callback(
+        &self,
+        _editor: &mut Editor,
+        _plugin_name: String,
+        _callback_id: u64,
+        _value: serde_json::Value,
+    ) -> Result<()> {
+        // No UI-callback host import exists for WASM plugins yet (see the
+        // module doc comment), so there's nothing to route here.
+        Ok(())
+    }
+
+    fn get_commands(&self) -> Vec<CommandMetadata> {
+        self.commands.lock().values().map(|(meta, _)| meta.clone()).collect()
+    }
+
+    fn get_commands_for_plugin(&self, plugin_name: &str) -> Vec<CommandMetadata> {
+        self.commands
+            .lock()
+            .values()
+            .filter(|(_, owner)| owner == plugin_name)
+            .map(|(meta, _)| meta.clone())
+            .collect()
+    }
+
+    fn plugins(&self) -> Vec<Plugin> {
+        self.plugins
+            .lock()
+            .values()
+            .map(|loaded| loaded.plugin.clone())
+            .collect()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
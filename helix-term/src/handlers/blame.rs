@@ -1,21 +1,128 @@
-use std::{mem, time::Duration};
+use std::{collections::HashMap, mem, time::Duration};
 
+use helix_core::Operation;
 use helix_event::register_hook;
 use helix_vcs::FileBlame;
 use helix_view::{
-    events::{DocumentDidOpen, EditorConfigDidChange},
+    events::{DocumentDidChange, DocumentDidOpen, EditorConfigDidChange},
     handlers::{BlameEvent, Handlers},
     DocumentId,
 };
 use tokio::time::Instant;
 
+use crate::compositor::Compositor;
 use crate::job;
+use crate::ui::{Popup, Text};
+
+/// Translates a *current* line number back to the line it was blamed at,
+/// given every net line insertion/deletion recorded since blame was last
+/// computed. Lines that were themselves touched by a local edit have no
+/// sensible original line and should report "Not Committed Yet" instead.
+#[derive(Default, Clone)]
+struct LineDisplacementMap {
+    /// Sorted by `line`; `cumulative` is the total net delta from every
+    /// edit at or before `line`, so a lookup is one binary search away.
+    entries: Vec<(usize, isize)>,
+    /// Lines touched by a local edit since the last recompute.
+    dirty: std::collections::BTreeSet<usize>,
+}
+
+impl LineDisplacementMap {
+    /// Record that `delta` lines were net inserted (positive) or deleted
+    /// (negative) at `at_line`.
+    fn record_edit(&mut self, at_line: usize, delta: isize) {
+        // A net insertion also touches every line it added below `at_line`.
+        let added = usize::try_from(delta).unwrap_or(0);
+        self.dirty.extend(at_line..=at_line + added);
+        if delta == 0 {
+            return;
+        }
+        match self.entries.binary_search_by_key(&at_line, |&(l, _)| l) {
+            Ok(i) => {
+                for (_, cumulative) in &mut self.entries[i..] {
+                    *cumulative += delta;
+                }
+            }
+            Err(i) => {
+                let running = self.entries.get(i.wrapping_sub(1)).map_or(0, |&(_, c)| c);
+                self.entries.insert(i, (at_line, running + delta));
+                for (_, cumulative) in &mut self.entries[i + 1..] {
+                    *cumulative += delta;
+                }
+            }
+        }
+    }
+
+    /// Translate `current_line` back to the line it was blamed at, or
+    /// `None` if that line was itself touched by an uncommitted edit.
+    fn translate(&self, current_line: usize) -> Option<usize> {
+        if self.dirty.contains(&current_line) {
+            return None;
+        }
+        let shift = match self.entries.binary_search_by_key(&current_line, |&(l, _)| l) {
+            Ok(i) => self.entries[i].1,
+            Err(0) => 0,
+            Err(i) => self.entries[i - 1].1,
+        };
+        usize::try_from(current_line as isize - shift).ok()
+    }
+}
+
+/// A cached blame result plus the displacement map that keeps it accurate
+/// across edits, valid only as long as `head` still matches the repo's
+/// current HEAD commit. A file save should also invalidate this (committing
+/// elsewhere can change blame attribution without moving HEAD locally), but
+/// that needs a `DocumentDidSave`-style hook wired up by whatever dispatches
+/// `BlameEvent`; this cache is HEAD-keyed in the meantime.
+#[derive(Default)]
+struct CachedBlame {
+    head: String,
+    displacement: LineDisplacementMap,
+}
+
+/// Read the repository HEAD commit for the repo containing `path`, used to
+/// decide whether a cached blame is still valid. Shells out to `git`
+/// directly rather than pulling in a full git library just for this check.
+fn current_head(path: &std::path::Path) -> Option<String> {
+    let dir = path.parent()?;
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
 
 #[derive(Default)]
 pub struct BlameHandler {
     pending_path: Option<std::path::PathBuf>,
     doc_id: DocumentId,
     show_blame_for_line_in_statusline: Option<u32>,
+    /// Set when the pending request came from [`request_commit_popup`]
+    /// rather than the inline statusline blame, so `finish_debounce` shows a
+    /// floating popup instead of just setting the status line.
+    show_commit_popup: bool,
+    /// Per-document cache, keyed by the HEAD commit blame was last computed
+    /// against, so an edit or a `DocumentDidOpen` doesn't force a full
+    /// `FileBlame::try_new` re-spawn unless HEAD actually moved.
+    cache: HashMap<DocumentId, CachedBlame>,
+}
+
+impl BlameHandler {
+    /// Record a net line insertion/deletion for `doc_id`'s displacement map.
+    /// Driven by the `DocumentDidChange` hook in [`register_hooks`] so
+    /// unchanged lines keep reporting correct blame across the editing
+    /// session instead of only at the next full recompute.
+    pub fn record_edit(&mut self, doc_id: DocumentId, at_line: usize, delta: isize) {
+        self.cache
+            .entry(doc_id)
+            .or_default()
+            .displacement
+            .record_edit(at_line, delta);
+    }
 }
 
 impl helix_event::AsyncHook for BlameHandler {
@@ -24,10 +131,19 @@ impl helix_event::AsyncHook for BlameHandler {
     fn handle_event(
         &mut self,
         event: Self::Event,
-        _timeout: Option<tokio::time::Instant>,
+        timeout: Option<tokio::time::Instant>,
     ) -> Option<tokio::time::Instant> {
+        // A per-edit notification rather than a blame request: fold it into
+        // the displacement map immediately and leave any debounce already in
+        // flight for a real recompute untouched.
+        if let Some((at_line, delta)) = event.edit {
+            self.record_edit(event.doc_id, at_line, delta);
+            return timeout;
+        }
+
         self.doc_id = event.doc_id;
         self.show_blame_for_line_in_statusline = event.line;
+        self.show_commit_popup = event.popup;
         self.pending_path = Some(event.path);
         Some(Instant::now() + Duration::from_millis(50))
     }
@@ -35,9 +151,25 @@ impl helix_event::AsyncHook for BlameHandler {
     fn finish_debounce(&mut self) {
         let doc_id = self.doc_id;
         let line_blame = self.show_blame_for_line_in_statusline;
+        let show_popup = self.show_commit_popup;
         let path = mem::take(&mut self.pending_path);
-        if let Some(path) = path {
-            job::dispatch_blocking(move |editor, _| {
+        let Some(path) = path else { return };
+
+        let head = current_head(&path);
+        let cache_hit = head
+            .as_deref()
+            .zip(self.cache.get(&doc_id))
+            .is_some_and(|(head, entry)| entry.head == head);
+
+        if !cache_hit {
+            self.cache.insert(
+                doc_id,
+                CachedBlame {
+                    head: head.unwrap_or_default(),
+                    displacement: LineDisplacementMap::default(),
+                },
+            );
+            job::dispatch_blocking(move |editor, compositor| {
                 let Some(doc) = editor.document_mut(doc_id) else {
                     return;
                 };
@@ -45,16 +177,62 @@ impl helix_event::AsyncHook for BlameHandler {
                 doc.file_blame = Some(result);
                 if !editor.config().inline_blame.auto_fetch {
                     if let Some(line) = line_blame {
-                        crate::commands::blame_line_impl(editor, doc_id, line);
+                        if show_popup {
+                            show_commit_popup(editor, compositor, doc_id, line);
+                        } else {
+                            crate::commands::blame_line_impl(editor, doc_id, line);
+                        }
                     } else {
                         editor.set_status("Blame for this file is now available")
                     }
                 }
             });
+            return;
+        }
+
+        // HEAD hasn't moved: reuse the blame already sitting on the
+        // document and just remap the requested line through whatever
+        // local edits have happened since it was computed.
+        if let Some(line) = line_blame {
+            let original_line = self
+                .cache
+                .get(&doc_id)
+                .and_then(|entry| entry.displacement.translate(line as usize));
+            job::dispatch_blocking(move |editor, compositor| match original_line {
+                Some(original_line) => {
+                    if show_popup {
+                        show_commit_popup(editor, compositor, doc_id, original_line as u32);
+                    } else {
+                        crate::commands::blame_line_impl(editor, doc_id, original_line as u32);
+                    }
+                }
+                None => editor.set_status("Not Committed Yet"),
+            });
         }
     }
 }
 
+/// Request the full commit-detail popup for `line` of `doc_id`, the
+/// command a `:blame-popup`/commit-detail keybind calls. Goes through the
+/// same debounced `BlameEvent` pipeline as the statusline blame so a cached
+/// blame (or one already in flight) is reused instead of re-spawning
+/// `FileBlame::try_new`.
+pub fn request_commit_popup(handlers: &Handlers, editor: &Editor, doc_id: DocumentId, line: u32) {
+    let Some(path) = editor.document(doc_id).and_then(|doc| doc.path()) else {
+        return;
+    };
+    helix_event::send_blocking(
+        &handlers.blame,
+        BlameEvent {
+            path: path.to_path_buf(),
+            doc_id,
+            line: Some(line),
+            edit: None,
+            popup: true,
+        },
+    );
+}
+
 pub(super) fn register_hooks(handlers: &Handlers) {
     let tx = handlers.blame.clone();
     register_hook!(move |event: &mut DocumentDidOpen<'_>| {
@@ -65,6 +243,37 @@ pub(super) fn register_hooks(handlers: &Handlers) {
                     path: event.path.to_path_buf(),
                     doc_id: event.doc,
                     line: None,
+                    edit: None,
+                    popup: false,
+                },
+            );
+        }
+        Ok(())
+    });
+    let tx = handlers.blame.clone();
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if let Some(path) = event.doc.path() {
+            let old_line_count = event.old_doc.len_lines();
+            let new_line_count = event.doc.text().len_lines();
+            let delta = new_line_count as isize - old_line_count as isize;
+
+            let mut at_char = 0;
+            for op in event.changes.changes() {
+                match op {
+                    Operation::Retain(n) => at_char += n,
+                    Operation::Delete(_) | Operation::Insert(_) => break,
+                }
+            }
+            let at_line = event.old_doc.char_to_line(at_char.min(event.old_doc.len_chars()));
+
+            helix_event::send_blocking(
+                &tx,
+                BlameEvent {
+                    path: path.to_path_buf(),
+                    doc_id: event.doc.id(),
+                    line: None,
+                    edit: Some((at_line, delta)),
+                    popup: false,
                 },
             );
         }
@@ -86,6 +295,8 @@ pub(super) fn register_hooks(handlers: &Handlers) {
                             path: path.to_path_buf(),
                             doc_id: doc.id(),
                             line: None,
+                            edit: None,
+                            popup: false,
                         },
                     );
                 }
@@ -94,3 +305,148 @@ pub(super) fn register_hooks(handlers: &Handlers) {
         Ok(())
     });
 }
+
+/// `:blame-popup` / the commit-detail keybind calls this with the already
+/// resolved editor and compositor (see `job::dispatch_blocking`'s second
+/// argument) to show the full commit detail for `line` of `doc_id` in a
+/// floating popup, selectable hash first so it can be yanked straight out
+/// of the popup like any other selectable text.
+pub(crate) fn show_commit_popup(editor: &mut Editor, compositor: &mut Compositor, doc_id: DocumentId, line: u32) {
+    let now_unix = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let Some(doc) = editor.document(doc_id) else {
+        return;
+    };
+    let Some(Ok(blame)) = doc.file_blame.as_ref() else {
+        editor.set_status("No blame information for this file yet");
+        return;
+    };
+    let Some(commit) = blame.blame_for_line(line as usize) else {
+        editor.set_status("No commit found for this line");
+        return;
+    };
+
+    let (subject, body) = commit
+        .message
+        .split_once("\n\n")
+        .unwrap_or((commit.message.as_str(), ""));
+
+    let text = format_commit_popup(
+        &commit.short_commit_hash,
+        &commit.author,
+        &commit.author_email,
+        commit.author_time,
+        now_unix,
+        subject,
+        body,
+        72,
+    );
+
+    let popup = Popup::new("blame-commit-popup", Text::new(text)).auto_close(true);
+    compositor.replace_or_push("blame-commit-popup", popup);
+}
+
+/// Render the full detail for a single commit the way a hover popup over a
+/// blame line would: short hash, author + email, relative and absolute
+/// dates, subject, and a word-wrapped body. Called from [`show_commit_popup`]
+/// with data pulled out of the same `FileBlame` this handler already
+/// fetches, so showing the popup never dispatches a second async lookup.
+pub(crate) fn format_commit_popup(
+    short_hash: &str,
+    author: &str,
+    email: &str,
+    authored_unix: i64,
+    now_unix: i64,
+    subject: &str,
+    body: &str,
+    wrap_width: usize,
+) -> String {
+    let relative = format_relative_time(now_unix.saturating_sub(authored_unix));
+    let absolute = format_absolute_time(authored_unix);
+
+    let mut out = format!(
+        "{short_hash} {subject}\n{author} <{email}>\n{relative} ({absolute})",
+    );
+
+    if !body.trim().is_empty() {
+        out.push_str("\n\n");
+        out.push_str(&wrap_body(body, wrap_width));
+    }
+
+    out
+}
+
+fn format_relative_time(seconds_ago: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    match seconds_ago {
+        s if s < MINUTE => "just now".to_string(),
+        s if s < HOUR => format!("{} minute(s) ago", s / MINUTE),
+        s if s < DAY => format!("{} hour(s) ago", s / HOUR),
+        s if s < MONTH => format!("{} day(s) ago", s / DAY),
+        s if s < YEAR => format!("{} month(s) ago", s / MONTH),
+        s => format!("{} year(s) ago", s / YEAR),
+    }
+}
+
+fn format_absolute_time(unix_seconds: i64) -> String {
+    // Days-since-epoch civil-date conversion (Howard Hinnant's algorithm),
+    // so this doesn't need to pull in a full datetime crate just to print a
+    // popup timestamp.
+    let days = unix_seconds.div_euclid(86_400);
+    let secs_of_day = unix_seconds.rem_euclid(86_400);
+    let (y, m, d) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        y,
+        m,
+        d,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+fn wrap_body(body: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for paragraph in body.trim().lines() {
+        if paragraph.is_empty() {
+            lines.push(String::new());
+            continue;
+        }
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            if !current.is_empty() && current.len() + 1 + word.len() > width {
+                lines.push(mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+    }
+    lines.join("\n")
+}
@@ -2,11 +2,21 @@ use crate::compositor::Context;
 use crate::job::Jobs;
 use crate::ui::PromptEvent;
 use helix_core::command_line::Args;
-use helix_plugin::types::{EditorCommandRegistry, UiHandler};
+use helix_plugin::types::{
+    DockSide, EditorCommandRegistry, InlineInputAnchor, PickerItem, PickerOptions,
+    ResolvedPanelWidget, ResolvedPopover, UiHandler,
+};
 use helix_view::Editor;
 use std::sync::Arc;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
+/// Requests a plugin's UI call turns into, drained by whatever owns the
+/// compositor (the main application event loop) to actually show the
+/// widget. That loop isn't part of this crate, so `TermUiHandler` only gets
+/// as far as forwarding a fully-described request down `sender`; driving
+/// `Picker` here with real incremental fuzzy matching, multi-select, and a
+/// live preview pane is the receiving loop's job once it reads a `Picker`
+/// request off this channel.
 pub enum UiRequest {
     Prompt {
         message: String,
@@ -20,11 +30,35 @@ pub enum UiRequest {
         callback_id: u64,
     },
     Picker {
-        items: Vec<String>,
+        items: Vec<PickerItem>,
+        options: PickerOptions,
+        plugin_name: String,
+        callback_id: u64,
+    },
+    /// An editable input field anchored over a buffer range, rendered in
+    /// place the way `PluginLineAnnotations`/`PluginDecoration` already
+    /// reserve and draw virtual lines against an anchored line - pushing
+    /// the lines below it down to make room for the field.
+    InlineInput {
+        anchor: InlineInputAnchor,
         prompt: String,
+        default: Option<String>,
         plugin_name: String,
         callback_id: u64,
     },
+    /// Display (or refresh) a plugin's docked panel, already laid out by
+    /// `helix.ui.create_panel` - see `UiRequest` top comment.
+    Panel {
+        dock: DockSide,
+        widgets: Vec<ResolvedPanelWidget>,
+        plugin_name: String,
+    },
+    /// Show a floating popover, already placed and sized by
+    /// `helix.ui.popover` - see `UiRequest` top comment.
+    Popover {
+        resolved: ResolvedPopover,
+        plugin_name: String,
+    },
 }
 
 pub struct TermUiHandler {
@@ -65,18 +99,57 @@ impl UiHandler for TermUiHandler {
     fn picker(
         &self,
         _editor: &mut Editor,
-        items: Vec<String>,
-        prompt: String,
+        items: Vec<PickerItem>,
+        options: PickerOptions,
         plugin_name: String,
         callback_id: u64,
     ) {
         let _ = self.sender.send(UiRequest::Picker {
             items,
+            options,
+            plugin_name,
+            callback_id,
+        });
+    }
+
+    fn inline_input(
+        &self,
+        _editor: &mut Editor,
+        anchor: InlineInputAnchor,
+        prompt: String,
+        default: Option<String>,
+        plugin_name: String,
+        callback_id: u64,
+    ) {
+        let _ = self.sender.send(UiRequest::InlineInput {
+            anchor,
             prompt,
+            default,
             plugin_name,
             callback_id,
         });
     }
+
+    fn create_panel(
+        &self,
+        _editor: &mut Editor,
+        plugin_name: String,
+        dock: DockSide,
+        widgets: Vec<ResolvedPanelWidget>,
+    ) {
+        let _ = self.sender.send(UiRequest::Panel {
+            dock,
+            widgets,
+            plugin_name,
+        });
+    }
+
+    fn popover(&self, _editor: &mut Editor, plugin_name: String, resolved: ResolvedPopover) {
+        let _ = self.sender.send(UiRequest::Popover {
+            resolved,
+            plugin_name,
+        });
+    }
 }
 
 pub struct TermCommandRegistry {}
@@ -3,6 +3,7 @@ use crate::ui::text_decorations::Decoration;
 use helix_core::doc_formatter::{DocumentFormatter, FormattedGrapheme, TextFormat};
 use helix_core::text_annotations::TextAnnotations;
 use helix_core::Position;
+use helix_view::annotations::ansi;
 use helix_view::{Document, Theme, ViewId};
 use std::collections::BTreeMap;
 
@@ -12,20 +13,38 @@ pub struct PluginDecoration<'a> {
     view_id: ViewId,
     anchor_idx: usize,
     anchors: Vec<usize>,
+    /// `[start, end)` absolute char ranges for column-range underline/squiggle
+    /// annotations, sorted by start. Handled in `decorate_grapheme` rather
+    /// than `render_virt_lines`, since these style existing code-line cells
+    /// in place instead of inserting or replacing text.
+    underline_spans: Vec<(usize, usize, helix_view::theme::Style)>,
 }
 
 impl<'a> PluginDecoration<'a> {
     pub fn new(doc: &'a Document, theme: &'a Theme, view_id: ViewId) -> Self {
         let mut anchors = Vec::new();
+        let mut underline_spans = Vec::new();
         if let Some(annots) = doc.plugin_annotations.get(&view_id) {
             for annot in annots {
                 if annot.is_line {
                     anchors.push(annot.char_idx);
                 }
+                if let Some(underline) = &annot.underline {
+                    let line = doc.text().char_to_line(annot.char_idx);
+                    let line_start = doc.text().line_to_char(line);
+                    let start = line_start + underline.start_col as usize;
+                    let end = line_start + underline.end_col as usize;
+                    if end > start {
+                        anchors.push(start);
+                        anchors.push(end);
+                        underline_spans.push((start, end, underline_style(theme, underline)));
+                    }
+                }
             }
         }
         anchors.sort_unstable();
         anchors.dedup();
+        underline_spans.sort_by_key(|&(start, _, _)| start);
 
         Self {
             doc,
@@ -33,6 +52,7 @@ impl<'a> PluginDecoration<'a> {
             view_id,
             anchor_idx: 0,
             anchors,
+            underline_spans,
         }
     }
 
@@ -62,6 +82,115 @@ impl<'a> PluginDecoration<'a> {
     }
 }
 
+/// Parse `text` for ANSI SGR escapes and return the escape-free text
+/// alongside `(start_char, end_char, style)` ranges describing it, with
+/// each span's style patched on top of `base_style` so a plugin's ANSI
+/// codes layer on top of its `fg`/`bg`/`style` fields (and the theme)
+/// rather than replacing them outright.
+fn styled_ranges(
+    text: &str,
+    base_style: helix_view::theme::Style,
+) -> (String, Vec<(usize, usize, helix_view::theme::Style)>) {
+    let spans = ansi::parse_ansi(text);
+    let mut plain = String::with_capacity(text.len());
+    let mut ranges = Vec::with_capacity(spans.len());
+    let mut offset = 0;
+    for span in spans {
+        let len = span.text.chars().count();
+        if len > 0 {
+            ranges.push((offset, offset + len, base_style.patch(span.style)));
+        }
+        plain.push_str(&span.text);
+        offset += len;
+    }
+    (plain, ranges)
+}
+
+/// Look up the style in effect at `char_idx` (an offset into the plain
+/// text `styled_ranges` was built from), falling back to `base_style` for
+/// any stretch an ANSI span didn't cover.
+fn style_at(
+    ranges: &[(usize, usize, helix_view::theme::Style)],
+    char_idx: usize,
+    base_style: helix_view::theme::Style,
+) -> helix_view::theme::Style {
+    ranges
+        .iter()
+        .find(|(start, end, _)| char_idx >= *start && char_idx < *end)
+        .map(|(_, _, style)| *style)
+        .unwrap_or(base_style)
+}
+
+/// Horizontal screen column to start drawing an `Above`-placed block at,
+/// given its `align`. `Left` keeps the existing offset-anchored behavior;
+/// `Center`/`Right` position the block within the full viewport width
+/// using the same conservative char-count width estimate used elsewhere
+/// in this file; `Indent` matches the leading whitespace of the line the
+/// block is anchored to.
+fn above_block_draw_col(
+    align: helix_view::annotations::plugins::AnnotationAlign,
+    annot: &helix_view::document::PluginAnnotation,
+    doc: &Document,
+    next_line_start: usize,
+    viewport_width: u16,
+    offset_col: usize,
+) -> u16 {
+    use helix_view::annotations::plugins::AnnotationAlign;
+
+    let abs_col = match align {
+        AnnotationAlign::Left => annot.offset as usize,
+        AnnotationAlign::Center | AnnotationAlign::Right => {
+            let total_chars = annot.text.chars().count();
+            // Conservative estimate: some chars may be 2 columns wide.
+            let estimated_width = total_chars + total_chars / 4;
+            let viewport_width = viewport_width as usize;
+            if align == AnnotationAlign::Center {
+                (viewport_width.saturating_sub(estimated_width)) / 2
+            } else {
+                viewport_width.saturating_sub(estimated_width)
+            }
+        }
+        AnnotationAlign::Indent => {
+            let line = doc.text().line(doc.text().char_to_line(next_line_start));
+            line.chars().take_while(|c| *c == ' ' || *c == '\t').count()
+        }
+    };
+
+    abs_col.saturating_sub(offset_col) as u16
+}
+
+/// Build the style for a column-range underline/squiggle span: the
+/// requested underline mode, in either the annotation's own color or the
+/// theme's default diagnostic color, the same way rustc's annotate-snippet
+/// emitter colors its carets.
+fn underline_style(
+    theme: &Theme,
+    underline: &helix_view::annotations::plugins::PluginUnderline,
+) -> helix_view::theme::Style {
+    use helix_view::annotations::plugins::UnderlineKind;
+    use helix_view::graphics::UnderlineStyle;
+
+    let mut style = helix_view::theme::Style::default().underline_style(match underline.kind {
+        UnderlineKind::Straight => UnderlineStyle::Line,
+        UnderlineKind::Double => UnderlineStyle::DoubleLine,
+        UnderlineKind::Curly => UnderlineStyle::Curl,
+    });
+
+    if let Some(color) = underline
+        .color
+        .as_deref()
+        .and_then(|c| helix_view::graphics::Color::from_hex(c).ok())
+    {
+        style = style.underline_color(color);
+    } else if let Some(diagnostic_style) = theme.try_get("diagnostic") {
+        if let Some(color) = diagnostic_style.underline_color.or(diagnostic_style.fg) {
+            style = style.underline_color(color);
+        }
+    }
+
+    style
+}
+
 impl Decoration for PluginDecoration<'_> {
     fn render_virt_lines(
         &mut self,
@@ -124,7 +253,7 @@ impl Decoration for PluginDecoration<'_> {
 
             // Second pass: render all inline annotations with consistent drop decision
             for annot in &inline_annots {
-                let style = self.build_style(annot);
+                let base_style = self.build_style(annot);
                 let dropped = should_drop_all;
 
                 // Use dropped_text if available and annotation is dropped, otherwise use text
@@ -169,8 +298,9 @@ impl Decoration for PluginDecoration<'_> {
                     soft_wrap_at_text_width: true,
                 };
 
+                let (plain_text, styled_ranges) = styled_ranges(display_text, base_style);
                 let annotations = TextAnnotations::default();
-                let rope = helix_core::Rope::from(display_text.as_str());
+                let rope = helix_core::Rope::from(plain_text.as_str());
                 let formatter = DocumentFormatter::new_at_prev_checkpoint(
                     rope.slice(..),
                     &text_fmt,
@@ -193,6 +323,7 @@ impl Decoration for PluginDecoration<'_> {
                         // Non-dropped rows 1+: render on virtual lines
                         pos.visual_line + virt_off.row as u16 + (grapheme.visual_pos.row as u16 - 1)
                     };
+                    let style = style_at(&styled_ranges, grapheme.char_idx, base_style);
                     renderer.draw_decoration_grapheme(
                         grapheme.raw,
                         style,
@@ -222,8 +353,18 @@ impl Decoration for PluginDecoration<'_> {
             let mut max_virt_idx: i32 = -1;
             let mut next_auto_idx: u16 = 0;
 
-            // Collect all virtual line annotations
-            let virt_annots: Vec<_> = line_annots.iter().filter(|a| a.is_line).copied().collect();
+            // Collect this line's own virtual line annotations. `Above`-placed
+            // annotations anchored to the *next* line are drawn further down,
+            // in the same reserved space (see `PluginLineAnnotations`, which
+            // reserves their height against this line's trailing rows).
+            let virt_annots: Vec<_> = line_annots
+                .iter()
+                .filter(|a| {
+                    a.is_line
+                        && a.placement == helix_view::annotations::plugins::AnnotationPlacement::Below
+                })
+                .copied()
+                .collect();
 
             // Find the max explicit virt_line_idx
             for annot in &virt_annots {
@@ -250,7 +391,7 @@ impl Decoration for PluginDecoration<'_> {
                 let mut max_height_in_row: u16 = 0;
 
                 for annot in annots_in_row {
-                    let style = self.build_style(annot);
+                    let base_style = self.build_style(annot);
                     let abs_text_col = annot.offset as usize;
                     let available_width = renderer.viewport.width.saturating_sub(annot.offset);
 
@@ -272,8 +413,9 @@ impl Decoration for PluginDecoration<'_> {
                         soft_wrap_at_text_width: true,
                     };
 
+                    let (plain_text, styled_ranges) = styled_ranges(&annot.text, base_style);
                     let annotations = TextAnnotations::default();
-                    let rope = helix_core::Rope::from(annot.text.as_str());
+                    let rope = helix_core::Rope::from(plain_text.as_str());
                     let formatter = DocumentFormatter::new_at_prev_checkpoint(
                         rope.slice(..),
                         &text_fmt,
@@ -284,6 +426,7 @@ impl Decoration for PluginDecoration<'_> {
                     let mut last_row = 0;
                     for grapheme in formatter {
                         last_row = grapheme.visual_pos.row;
+                        let style = style_at(&styled_ranges, grapheme.char_idx, base_style);
                         renderer.draw_decoration_grapheme(
                             grapheme.raw,
                             style,
@@ -298,6 +441,69 @@ impl Decoration for PluginDecoration<'_> {
                 }
                 cumulative_row_offset += max_height_in_row;
             }
+
+            // Draw `Above`-placed annotations anchored to the *next* line,
+            // occupying the rows `PluginLineAnnotations` reserved for them
+            // right after this line's own trailing virtual lines.
+            let total_lines = self.doc.text().len_lines();
+            if pos.doc_line + 1 < total_lines {
+                let next_line_start = line_end;
+                let next_line_end = self.doc.text().line_to_char(pos.doc_line + 2);
+                for annot in annots.iter().filter(|a| {
+                    a.is_line
+                        && a.placement == helix_view::annotations::plugins::AnnotationPlacement::Above
+                        && a.char_idx >= next_line_start
+                        && a.char_idx < next_line_end
+                }) {
+                    let base_style = self.build_style(annot);
+                    let available_width = renderer.viewport.width.saturating_sub(annot.offset);
+                    if available_width == 0 {
+                        continue;
+                    }
+                    let draw_col = above_block_draw_col(
+                        annot.align,
+                        annot,
+                        self.doc,
+                        next_line_start,
+                        renderer.viewport.width,
+                        renderer.offset.col,
+                    );
+                    let text_fmt = TextFormat {
+                        soft_wrap: true,
+                        tab_width: self.doc.tab_width() as u16,
+                        max_wrap: available_width.saturating_div(4).max(20),
+                        max_indent_retain: 0,
+                        wrap_indicator_highlight: None,
+                        viewport_width: available_width,
+                        soft_wrap_at_text_width: true,
+                    };
+                    let (plain_text, styled_ranges) = styled_ranges(&annot.text, base_style);
+                    let annotations = TextAnnotations::default();
+                    let rope = helix_core::Rope::from(plain_text.as_str());
+                    let formatter = DocumentFormatter::new_at_prev_checkpoint(
+                        rope.slice(..),
+                        &text_fmt,
+                        &annotations,
+                        0,
+                    );
+                    let mut last_row = 0;
+                    for grapheme in formatter {
+                        last_row = grapheme.visual_pos.row;
+                        let style = style_at(&styled_ranges, grapheme.char_idx, base_style);
+                        renderer.draw_decoration_grapheme(
+                            grapheme.raw,
+                            style,
+                            pos.visual_line
+                                + virt_off.row as u16
+                                + cumulative_row_offset
+                                + grapheme.visual_pos.row as u16,
+                            draw_col + grapheme.visual_pos.col as u16,
+                        );
+                    }
+                    cumulative_row_offset += last_row as u16 + 1;
+                }
+            }
+
             virt_lines_drawn = cumulative_row_offset as usize;
         }
 
@@ -314,12 +520,24 @@ impl Decoration for PluginDecoration<'_> {
 
     fn decorate_grapheme(
         &mut self,
-        _renderer: &mut TextRenderer,
+        renderer: &mut TextRenderer,
         grapheme: &FormattedGrapheme,
     ) -> usize {
         if self.anchors.get(self.anchor_idx) == Some(&grapheme.char_idx) {
             self.anchor_idx += 1;
         }
+
+        if let Some((_, _, style)) = self
+            .underline_spans
+            .iter()
+            .find(|(start, end, _)| grapheme.char_idx >= *start && grapheme.char_idx < *end)
+        {
+            renderer.set_style(grapheme.visual_pos, *style);
+            // Keep being called for every grapheme inside the span (rather
+            // than skipping to the next anchor) so each cell gets styled.
+            return grapheme.char_idx + 1;
+        }
+
         self.anchors
             .get(self.anchor_idx)
             .cloned()
@@ -0,0 +1,252 @@
+//! ANSI SGR (Select Graphic Rendition) escape-sequence parsing for
+//! plugin-emitted text. Plugins often wrap tool output they didn't generate
+//! themselves (test runners, `git blame`, linters) and that output already
+//! carries ANSI color codes - rather than making every plugin strip or
+//! hand-translate those into `PluginAnnotation::fg`/`bg`/`style`, this lets
+//! `PluginDecoration` resolve the escapes into `Style`s directly at render
+//! time.
+
+use crate::graphics::{Color, Modifier, UnderlineStyle};
+use crate::theme::Style;
+
+/// One run of text sharing a single resolved `Style`, with escape
+/// sequences already stripped out of `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnsiSpan {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Split `input` into ANSI-styled runs. Each `ESC[...m` sequence updates a
+/// running `Style` (reset by code `0`, same as a real terminal) that's
+/// attached to every run of text until the next sequence. A malformed or
+/// unrecognized escape sequence is left in place as literal text rather
+/// than aborting the parse, so a plugin's typo doesn't eat the rest of its
+/// message.
+///
+/// Honors `NO_COLOR` (<https://no-color.org>): when set, returns the input
+/// with all escapes stripped and no styling applied at all.
+pub fn parse_ansi(input: &str) -> Vec<AnsiSpan> {
+    if std::env::var_os("NO_COLOR").is_some() {
+        return vec![AnsiSpan {
+            text: strip_ansi(input),
+            style: Style::default(),
+        }];
+    }
+
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut run = String::new();
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Scan a copy, so a malformed sequence leaves everything after
+            // `ESC[` in `chars` to be kept as literal text.
+            let mut lookahead = chars.clone();
+            match scan_sgr_params(&mut lookahead) {
+                Some(params) => {
+                    chars = lookahead;
+                    if !run.is_empty() {
+                        spans.push(AnsiSpan {
+                            text: std::mem::take(&mut run),
+                            style,
+                        });
+                    }
+                    apply_sgr(&mut style, &params);
+                }
+                None => {
+                    // Not a well-formed SGR sequence - keep it as text.
+                    run.push(c);
+                    run.push('[');
+                }
+            }
+            continue;
+        }
+        run.push(c);
+    }
+
+    if !run.is_empty() || spans.is_empty() {
+        spans.push(AnsiSpan { text: run, style });
+    }
+    spans
+}
+
+/// Remove SGR escape sequences from `input`, leaving the plain text
+/// behind. Used for `NO_COLOR` and by rendering surfaces (status messages,
+/// picker rows) that don't yet support per-run styling.
+pub fn strip_ansi(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            let mut lookahead = chars.clone();
+            if scan_sgr_params(&mut lookahead).is_some() {
+                chars = lookahead;
+                continue;
+            }
+            out.push(c);
+            out.push('[');
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Consume `params;params;...m` from an iterator positioned right after
+/// `ESC[`, returning the parameter text (without the trailing `m`) if the
+/// sequence is well-formed, or `None` (having consumed nothing beyond
+/// whatever digits/semicolons were seen) if it isn't an SGR sequence.
+fn scan_sgr_params(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    let mut params = String::new();
+    while let Some(&next) = chars.peek() {
+        if next == 'm' {
+            chars.next();
+            return Some(params);
+        }
+        if !(next.is_ascii_digit() || next == ';') {
+            return None;
+        }
+        params.push(next);
+        chars.next();
+    }
+    None
+}
+
+/// Apply one `ESC[...m` sequence's parameters onto a running `Style`,
+/// mirroring how a terminal interprets SGR codes left-to-right.
+fn apply_sgr(style: &mut Style, params: &str) {
+    let codes: Vec<u32> = if params.is_empty() {
+        vec![0]
+    } else {
+        params.split(';').map(|p| p.parse().unwrap_or(0)).collect()
+    };
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => *style = Style::default(),
+            1 => *style = style.add_modifier(Modifier::BOLD),
+            2 => *style = style.add_modifier(Modifier::DIM),
+            3 => *style = style.add_modifier(Modifier::ITALIC),
+            4 => *style = style.underline_style(UnderlineStyle::Line),
+            7 => *style = style.add_modifier(Modifier::REVERSED),
+            8 => *style = style.add_modifier(Modifier::HIDDEN),
+            9 => *style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => *style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => *style = style.remove_modifier(Modifier::ITALIC),
+            24 => style.underline_style = None,
+            28 => *style = style.remove_modifier(Modifier::HIDDEN),
+            29 => *style = style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg = Some(ansi_16_color(codes[i] - 30, false)),
+            38 => {
+                if let Some((color, consumed)) = parse_extended_color(&codes[i + 1..]) {
+                    style.fg = Some(color);
+                    i += consumed;
+                }
+            }
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(ansi_16_color(codes[i] - 40, false)),
+            48 => {
+                if let Some((color, consumed)) = parse_extended_color(&codes[i + 1..]) {
+                    style.bg = Some(color);
+                    i += consumed;
+                }
+            }
+            49 => style.bg = None,
+            90..=97 => style.fg = Some(ansi_16_color(codes[i] - 90, true)),
+            100..=107 => style.bg = Some(ansi_16_color(codes[i] - 100, true)),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Map a standard 16-color SGR index (0-7, the digit after the 3/4/9/10
+/// hundreds place) onto a `Color`, picking the bright variant for the
+/// 90-97/100-107 range.
+fn ansi_16_color(index: u32, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        (7, true) => Color::White,
+        _ => Color::Reset,
+    }
+}
+
+/// Parse the parameters following an extended color code (`38`/`48`):
+/// `5;N` for a 256-color palette index, or `2;R;G;B` for truecolor.
+/// Returns the resolved color plus how many of the *following* codes (not
+/// counting the `38`/`48` itself) it consumed.
+fn parse_extended_color(rest: &[u32]) -> Option<(Color, usize)> {
+    match rest.first() {
+        Some(5) => rest.get(1).map(|&n| (Color::Indexed(n as u8), 2)),
+        Some(2) if rest.len() >= 4 => {
+            Some((Color::Rgb(rest[1] as u8, rest[2] as u8, rest[3] as u8), 4))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_a_single_unstyled_span() {
+        let spans = parse_ansi("hello world");
+        assert_eq!(
+            spans,
+            vec![AnsiSpan {
+                text: "hello world".to_string(),
+                style: Style::default(),
+            }]
+        );
+    }
+
+    #[test]
+    fn basic_color_code_styles_the_following_run() {
+        let spans = parse_ansi("\u{1b}[31mfail\u{1b}[0m: 3 tests");
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].text, "fail");
+        assert_eq!(spans[0].style.fg, Some(Color::Red));
+        assert_eq!(spans[1].text, ": 3 tests");
+        assert_eq!(spans[1].style, Style::default());
+    }
+
+    #[test]
+    fn truecolor_and_bold_compose() {
+        let spans = parse_ansi("\u{1b}[1;38;2;10;20;30mhi");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].style.fg, Some(Color::Rgb(10, 20, 30)));
+        assert!(spans[0].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn malformed_sequence_is_kept_as_literal_text() {
+        let spans = parse_ansi("\u{1b}[not-a-code still visible");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "\u{1b}[not-a-code still visible");
+    }
+
+    #[test]
+    fn strip_ansi_removes_escapes_only() {
+        assert_eq!(strip_ansi("\u{1b}[32mok\u{1b}[0m"), "ok");
+    }
+}
@@ -1,9 +1,155 @@
-use crate::Document;
-use crate::ViewId;
+use crate::document::PluginAnnotation;
+use crate::{Document, DocumentId, ViewId};
 use helix_core::doc_formatter::{FormattedGrapheme, TextFormat};
 use helix_core::text_annotations::LineAnnotation;
 use helix_core::{softwrapped_dimensions, Position};
-use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+thread_local! {
+    /// Memoized `insert_virtual_lines` results, keyed by `ViewId`. Kept
+    /// external to `Document` (rather than as a field on it, the way
+    /// inlay hints are cached) since most documents have no plugin
+    /// annotations at all and shouldn't carry the extra bookkeeping.
+    static ANNOTATION_LAYOUT_CACHE: RefCell<HashMap<ViewId, ViewAnnotationCache>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A view's memoized per-line annotation layout, wholesale-invalidated
+/// when the document being shown, its revision (unless
+/// `invalidate_document_edit` already recorded the new one), the viewport
+/// width, or the tab width changes - any of those can change every line's layout,
+/// so there's no point tracking them more granularly.
+#[derive(Default)]
+struct ViewAnnotationCache {
+    doc_id: Option<DocumentId>,
+    revision: usize,
+    width: u16,
+    tab_width: usize,
+    lines: HashMap<usize, CachedLine>,
+}
+
+/// One memoized `insert_virtual_lines` result: the `Position` it returned,
+/// valid as long as the annotations laid out against this line (its own,
+/// plus `Above` ones anchored to the next line) still hash the same
+/// (`annotations_hash`) as when it was computed.
+struct CachedLine {
+    annotations_hash: u64,
+    position: Position,
+}
+
+/// Hash the subset of a line's plugin annotations that affects layout
+/// (text content and placement) - `fg`/`bg`/`style`/`underline`/`align`
+/// only affect how a line is drawn, not how much space it takes, so
+/// changing just those shouldn't invalidate the cached position.
+fn hash_line_annotations(annots: &[&PluginAnnotation]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for annot in annots {
+        annot.offset.hash(&mut hasher);
+        annot.is_line.hash(&mut hasher);
+        annot.placement.hash(&mut hasher);
+        annot.virt_line_idx.hash(&mut hasher);
+        annot.text.hash(&mut hasher);
+        annot.dropped_text.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Shift or drop this view's cached line positions after a document edit
+/// spanning `[start_line, start_line + removed_lines)`, replaced by
+/// `added_lines` new lines. Entries before the edit are untouched, entries
+/// inside it are dropped outright (their annotations may have moved or
+/// disappeared), and entries after it are shifted by the line-count delta
+/// so they keep pointing at the same logical line. Not yet wired to the
+/// document-apply path - a caller that tracks edits (e.g. an `on_change`
+/// hook) should call this right after applying a transaction, before the
+/// next render, so `insert_virtual_lines` doesn't need a full rescan.
+/// `revision` is the document's revision after the edit; recording it keeps
+/// `cached_virtual_lines` from clearing the shifted entries as stale.
+pub fn invalidate_document_edit(
+    view_id: ViewId,
+    revision: usize,
+    start_line: usize,
+    removed_lines: usize,
+    added_lines: usize,
+) {
+    ANNOTATION_LAYOUT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let Some(view_cache) = cache.get_mut(&view_id) else {
+            return;
+        };
+        view_cache.revision = revision;
+
+        let delta = added_lines as isize - removed_lines as isize;
+        let edited_end = start_line + removed_lines;
+
+        view_cache.lines = std::mem::take(&mut view_cache.lines)
+            .into_iter()
+            .filter_map(|(line, cached)| {
+                if line < start_line {
+                    Some((line, cached))
+                } else if line < edited_end {
+                    None
+                } else {
+                    Some(((line as isize + delta) as usize, cached))
+                }
+            })
+            .collect();
+    });
+}
+
+/// Underline style for a `[start_col, end_col)` span annotation, drawn the
+/// way rustc's annotate-snippet emitter draws diagnostic underlines -
+/// independent of the text-replacement/virtual-line annotation path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnderlineKind {
+    Straight,
+    Double,
+    Curly,
+}
+
+/// A column-range underline/squiggle on a single source line, carried by
+/// `helix_view::document::PluginAnnotation::underline`.
+#[derive(Debug, Clone)]
+pub struct PluginUnderline {
+    /// Start column on the annotation's anchored line, inclusive.
+    pub start_col: u16,
+    /// End column on the annotation's anchored line, exclusive.
+    pub end_col: u16,
+    pub kind: UnderlineKind,
+    /// Hex color (e.g. `"#ff0000"`), falling back to the theme's default
+    /// diagnostic color when unset.
+    pub color: Option<String>,
+}
+
+/// Where a line-level plugin annotation (`PluginAnnotation::is_line == true`)
+/// renders relative to the document line it's anchored to. Mirrors the
+/// `placement` field on `helix_view::document::PluginAnnotation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnnotationPlacement {
+    /// Rendered as virtual lines after the anchored line (the long-standing
+    /// behavior, and the default for annotations that don't specify one).
+    #[default]
+    Below,
+    /// Rendered as virtual lines before the anchored line.
+    Above,
+}
+
+/// How an `Above`-placed block annotation's text is horizontally positioned,
+/// set via `helix_view::document::PluginAnnotation::align`. Only meaningful
+/// for `AnnotationPlacement::Above` blocks - inline annotations and `Below`
+/// virtual lines still draw from `offset` regardless of this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnnotationAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Match the leading whitespace of the line the block is anchored to.
+    Indent,
+}
 
 pub struct PluginLineAnnotations<'a> {
     doc: &'a Document,
@@ -21,24 +167,93 @@ impl<'a> PluginLineAnnotations<'a> {
     }
 }
 
-impl LineAnnotation for PluginLineAnnotations<'_> {
-    fn reset_pos(&mut self, _char_idx: usize) -> usize {
-        usize::MAX
-    }
+impl PluginLineAnnotations<'_> {
+    /// Look up `doc_line`'s memoized layout in [`ANNOTATION_LAYOUT_CACHE`]
+    /// and return it if the document revision, viewport width, tab width,
+    /// and the annotations laid out against this line still match what produced it;
+    /// otherwise run [`Self::compute_virtual_lines`] and refresh the entry.
+    fn cached_virtual_lines(&self, line_end_visual_pos: Position, doc_line: usize) -> Position {
+        let doc_id = self.doc.id();
+        let revision = self.doc.get_current_revision();
+        let tab_width = self.doc.tab_width();
 
-    fn skip_concealed_anchors(&mut self, _conceal_end_char_idx: usize) -> usize {
-        usize::MAX
-    }
+        let annots_hash = self
+            .doc
+            .plugin_annotations
+            .get(&self.view_id)
+            .map(|annots| {
+                let line_start = self.doc.text().line_to_char(doc_line);
+                let line_end = self.doc.text().line_to_char(doc_line + 1);
+                // `Above` annotations on the next line take their height from
+                // this line's trailing rows, so they count here as well.
+                let next_line_end = if doc_line + 1 < self.doc.text().len_lines() {
+                    self.doc.text().line_to_char(doc_line + 2)
+                } else {
+                    line_end
+                };
+                let line_annots: Vec<_> = annots
+                    .iter()
+                    .filter(|a| {
+                        (a.char_idx >= line_start && a.char_idx < line_end)
+                            || (a.is_line
+                                && a.placement == AnnotationPlacement::Above
+                                && a.char_idx >= line_end
+                                && a.char_idx < next_line_end)
+                    })
+                    .collect();
+                hash_line_annotations(&line_annots)
+            })
+            .unwrap_or(0);
 
-    fn process_anchor(&mut self, _grapheme: &FormattedGrapheme) -> usize {
-        usize::MAX
+        let cached = ANNOTATION_LAYOUT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let view_cache = cache.entry(self.view_id).or_default();
+
+            if view_cache.doc_id != Some(doc_id)
+                || view_cache.revision != revision
+                || view_cache.width != self.width
+                || view_cache.tab_width != tab_width
+            {
+                view_cache.doc_id = Some(doc_id);
+                view_cache.revision = revision;
+                view_cache.width = self.width;
+                view_cache.tab_width = tab_width;
+                view_cache.lines.clear();
+            }
+
+            view_cache
+                .lines
+                .get(&doc_line)
+                .filter(|cached| cached.annotations_hash == annots_hash)
+                .map(|cached| cached.position)
+        });
+
+        if let Some(position) = cached {
+            return position;
+        }
+
+        let position = self.compute_virtual_lines(line_end_visual_pos, doc_line);
+
+        ANNOTATION_LAYOUT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(view_cache) = cache.get_mut(&self.view_id) {
+                view_cache.lines.insert(
+                    doc_line,
+                    CachedLine {
+                        annotations_hash: annots_hash,
+                        position,
+                    },
+                );
+            }
+        });
+
+        position
     }
-    fn insert_virtual_lines(
-        &mut self,
-        _line_end_char_idx: usize,
-        line_end_visual_pos: Position,
-        doc_line: usize,
-    ) -> Position {
+
+    /// Compute `doc_line`'s virtual-line layout from scratch by
+    /// soft-wrapping every plugin annotation anchored to it. Only called
+    /// on a cache miss - see [`Self::cached_virtual_lines`].
+    fn compute_virtual_lines(&self, line_end_visual_pos: Position, doc_line: usize) -> Position {
         let mut inline_extra_rows: u16 = 0;
         let mut virt_annots_by_row: BTreeMap<u16, Vec<_>> = BTreeMap::new();
         let mut max_virt_idx: i32 = -1;
@@ -133,8 +348,12 @@ impl LineAnnotation for PluginLineAnnotations<'_> {
                 }
             }
 
-            // 2. Group virtual line annotations
-            let virt_annots: Vec<_> = line_annots.iter().filter(|a| a.is_line).collect();
+            // 2. Group virtual line annotations. `Above`-placed annotations
+            // belong to the *next* line (handled below) rather than this one.
+            let virt_annots: Vec<_> = line_annots
+                .iter()
+                .filter(|a| a.is_line && a.placement == AnnotationPlacement::Below)
+                .collect();
             for annot in &virt_annots {
                 if let Some(idx) = annot.virt_line_idx {
                     max_virt_idx = max_virt_idx.max(idx as i32);
@@ -177,9 +396,64 @@ impl LineAnnotation for PluginLineAnnotations<'_> {
                 cumulative_row_offset += max_height_in_row;
             }
 
+            // `Above`-placed annotations anchored to the *next* line render
+            // before it, which means reserving their height here: Helix only
+            // lets a `LineAnnotation` insert virtual rows after the line it's
+            // given, so the space for "above line N+1" has to come from
+            // line N's trailing virtual lines.
+            let total_lines = self.doc.text().len_lines();
+            if doc_line + 1 < total_lines {
+                let next_line_start = line_end;
+                let next_line_end = self.doc.text().line_to_char(doc_line + 2);
+                for annot in annots
+                    .iter()
+                    .filter(|a| a.is_line && a.placement == AnnotationPlacement::Above)
+                    .filter(|a| a.char_idx >= next_line_start && a.char_idx < next_line_end)
+                {
+                    let available_width = self.width.saturating_sub(annot.offset);
+                    if available_width > 0 {
+                        let text_fmt = TextFormat {
+                            soft_wrap: true,
+                            tab_width: self.doc.tab_width() as u16,
+                            max_wrap: available_width.saturating_div(4).max(20),
+                            max_indent_retain: 0,
+                            wrap_indicator_highlight: None,
+                            viewport_width: available_width,
+                            soft_wrap_at_text_width: true,
+                        };
+                        let height =
+                            softwrapped_dimensions(annot.text.as_str().into(), &text_fmt).0;
+                        cumulative_row_offset += height as u16;
+                    }
+                }
+            }
+
             return Position::new(cumulative_row_offset as usize, 0);
         }
 
         Position::new(0, 0)
     }
 }
+
+impl LineAnnotation for PluginLineAnnotations<'_> {
+    fn reset_pos(&mut self, _char_idx: usize) -> usize {
+        usize::MAX
+    }
+
+    fn skip_concealed_anchors(&mut self, _conceal_end_char_idx: usize) -> usize {
+        usize::MAX
+    }
+
+    fn process_anchor(&mut self, _grapheme: &FormattedGrapheme) -> usize {
+        usize::MAX
+    }
+
+    fn insert_virtual_lines(
+        &mut self,
+        _line_end_char_idx: usize,
+        line_end_visual_pos: Position,
+        doc_line: usize,
+    ) -> Position {
+        self.cached_virtual_lines(line_end_visual_pos, doc_line)
+    }
+}